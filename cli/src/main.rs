@@ -1,8 +1,11 @@
 use anyhow::Context;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use csln::citation::Citations;
 use csln::from_file;
-use processor::{ProcReferences, Processor};
+use processor::{
+    Djot, Html, Latex, Markdown, Odf, Ooxml, PlainText, ProcReferences, Processor,
+    Renderer, Rtf, Selection, Typst,
+};
 
 #[derive(Parser, Default, Debug)]
 #[clap(author = "Bruce D'Arcus", version, about = "A CLI for CSLN")]
@@ -16,25 +19,80 @@ pub struct Opts {
     #[clap(short, long)]
     /// The optional path to the CSLN citation file
     citations: Option<String>,
+    #[clap(short, long, value_delimiter = ',')]
+    /// The ids of references to include without citing them, or "*" for all;
+    /// without a citation file, all references are included
+    nocite: Vec<String>,
     #[clap(short, long)]
     /// The path to the CSLN locale file
     locale: String,
+    #[clap(short, long, value_enum, default_value_t)]
+    /// The output format
+    format: OutputFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, Default, Debug)]
+pub enum OutputFormat {
+    /// The processed intermediate representation, as JSON
+    #[default]
+    Json,
+    /// Plain text
+    Plain,
+    /// HTML
+    Html,
+    /// LaTeX
+    Latex,
+    /// Djot
+    Djot,
+    /// CommonMark and Pandoc Markdown
+    Markdown,
+    /// Typst
+    Typst,
+    /// RTF, for pasting into word processors
+    Rtf,
+    /// Office Open XML (WordprocessingML) fragments
+    Ooxml,
+    /// OpenDocument text fragments
+    Odf,
 }
 
 fn main() -> anyhow::Result<()> {
     let opts = Opts::parse();
     let style = from_file(&opts.style).context("Failed to load style file")?;
-    let bibliography = from_file(&opts.bibliography).context("Failed to load bibliography file")?;
-    let citations: Citations = if let Some(citation_path) = opts.citations {
-        from_file(&citation_path).context("Failed to load citation file")?
-    } else {
-        Citations::default()
-    };
+    let bibliography =
+        from_file(&opts.bibliography).context("Failed to load bibliography file")?;
+    let (citations, selection): (Citations, Selection) =
+        if let Some(citation_path) = opts.citations {
+            let citations =
+                from_file(&citation_path).context("Failed to load citation file")?;
+            (citations, Selection::with_nocite(opts.nocite))
+        } else if opts.nocite.is_empty() {
+            (Citations::default(), Selection::All)
+        } else {
+            (Citations::default(), Selection::with_nocite(opts.nocite))
+        };
     let locale = from_file(&opts.locale).context("Failed to load locale file")?;
-    let processor: Processor = Processor::new(style, bibliography, citations, locale);
+    let processor: Processor =
+        Processor::new(style, bibliography, citations, locale).with_selection(selection);
     let rendered_refs: ProcReferences = processor.process_references();
-    let serialized_refs = serde_json::to_string_pretty(&rendered_refs)
-        .context("Failed to serialize references")?;
-    println!("{}", serialized_refs);
+    let render_options = processor.render_options();
+    let output = match opts.format {
+        OutputFormat::Json => serde_json::to_string_pretty(&rendered_refs)
+            .context("Failed to serialize references")?,
+        OutputFormat::Plain => {
+            PlainText.render_references(&rendered_refs, &render_options)
+        }
+        OutputFormat::Html => Html.render_references(&rendered_refs, &render_options),
+        OutputFormat::Latex => Latex.render_references(&rendered_refs, &render_options),
+        OutputFormat::Djot => Djot.render_references(&rendered_refs, &render_options),
+        OutputFormat::Markdown => {
+            Markdown.render_references(&rendered_refs, &render_options)
+        }
+        OutputFormat::Typst => Typst.render_references(&rendered_refs, &render_options),
+        OutputFormat::Rtf => Rtf.render_references(&rendered_refs, &render_options),
+        OutputFormat::Ooxml => Ooxml.render_references(&rendered_refs, &render_options),
+        OutputFormat::Odf => Odf.render_references(&rendered_refs, &render_options),
+    };
+    println!("{}", output);
     Ok(())
 }
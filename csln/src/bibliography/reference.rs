@@ -29,7 +29,9 @@ SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
 //! I would like to add ability to reference a parent by ID, but that is not yet implemented.
 
 use crate::style::locale::Locale;
-use crate::style::options::{AndOptions, AndOtherOptions, DisplayAsSort};
+use crate::style::options::{
+    AndOptions, AndOtherOptions, DisplayAsSort, GivenNameForm, ShortenListOptions,
+};
 use crate::style::{locale::MonthList, options::Config};
 use edtf::level_1::Edtf;
 use fmt::Display;
@@ -138,6 +140,42 @@ impl InputReference {
         }
     }
 
+    /// Return the type of the reference, as in the input; for example, "book".
+    pub fn ref_type(&self) -> String {
+        let ref_type = match self {
+            InputReference::Monograph(r) => serde_json::to_value(&r.r#type),
+            InputReference::CollectionComponent(r) => serde_json::to_value(&r.r#type),
+            InputReference::SerialComponent(r) => serde_json::to_value(&r.r#type),
+            InputReference::Collection(r) => serde_json::to_value(&r.r#type),
+        };
+        ref_type
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+
+    /// Return the DOI.
+    /// If the reference does not have a DOI, return None.
+    pub fn doi(&self) -> Option<String> {
+        match self {
+            InputReference::Monograph(r) => r.doi.clone(),
+            InputReference::CollectionComponent(r) => r.doi.clone(),
+            InputReference::SerialComponent(r) => r.doi.clone(),
+            _ => None,
+        }
+    }
+
+    /// Return the URL.
+    /// If the reference does not have a URL, return None.
+    pub fn url(&self) -> Option<Url> {
+        match self {
+            InputReference::Monograph(r) => r.url.clone(),
+            InputReference::CollectionComponent(r) => r.url.clone(),
+            InputReference::SerialComponent(r) => r.url.clone(),
+            InputReference::Collection(r) => r.url.clone(),
+        }
+    }
+
     pub fn set_id(&mut self, id: String) {
         match self {
             InputReference::Monograph(monograph) => monograph.id = Some(id),
@@ -414,6 +452,22 @@ impl EdtfString {
         }
     }
 
+    /// Return the year, month and day to sort the date by; missing parts are 0.
+    pub fn sort_key(&self) -> (i32, u32, u32) {
+        match self.parse() {
+            RefDate::Edtf(Edtf::Date(date)) => (
+                date.year(),
+                self.component_to_u32(date.month()),
+                self.component_to_u32(date.day()),
+            ),
+            RefDate::Edtf(Edtf::DateTime(datetime)) => {
+                let date = datetime.date();
+                (date.year(), date.month(), date.day())
+            }
+            parsed_date => (parsed_date.year(), 0, 0),
+        }
+    }
+
     fn month_to_string(month: u32, months: MonthList) -> String {
         if month > 0 {
             let index = month - 1;
@@ -489,6 +543,16 @@ fn year_months() {
     assert_eq!(date.year_month(months), "January 2020");
 }
 
+#[test]
+fn date_sort_keys() {
+    assert_eq!(EdtfString("2020-03-04".to_string()).sort_key(), (2020, 3, 4));
+    assert_eq!(EdtfString("2020-03".to_string()).sort_key(), (2020, 3, 0));
+    assert!(
+        EdtfString("2019-12-31".to_string()).sort_key()
+            < EdtfString("2020".to_string()).sort_key()
+    );
+}
+
 #[test]
 fn literal_dates() {
     let date_string = EdtfString("foo bar".to_string());
@@ -691,8 +755,9 @@ impl Contributor {
         let names = self.names(Config::default(), false);
         let mut result = names;
         if result.len() > 1 {
-            let last = result.pop().expect("List should have at least one element");
-            result.push(format!("{} {}", and, last));
+            if let Some(last) = result.pop() {
+                result.push(format!("{} {}", and, last));
+            }
         }
         result
     }
@@ -731,9 +796,75 @@ impl Contributor {
         }
     }
 
+    /// Return the family names of the contributors, as for citations.
+    pub fn family_names(&self) -> Vec<String> {
+        self.cite_names(GivenNameForm::None)
+    }
+
+    /// Return the names of the contributors as for citations, with the given
+    /// names in the given form before the family names.
+    pub fn cite_names(&self, given: GivenNameForm) -> Vec<String> {
+        match self {
+            Contributor::SimpleName(c) => vec![c.name.to_string()],
+            Contributor::StructuredName(contributor) => {
+                let given = match given {
+                    GivenNameForm::None => String::new(),
+                    GivenNameForm::Initials => {
+                        let initials = contributor.initials(Some(". ".to_string()));
+                        initials.trim_end().to_string()
+                    }
+                    GivenNameForm::Full => contributor.given.clone(),
+                };
+                if given.is_empty() {
+                    vec![contributor.family.to_string()]
+                } else {
+                    vec![format!("{} {}", given, contributor.family)]
+                }
+            }
+            Contributor::ContributorList(contributors) => {
+                contributors.0.iter().flat_map(|c| c.cite_names(given)).collect()
+            }
+        }
+    }
+
     pub fn format(&self, options: Config, locale: Locale) -> String {
         let as_sorted: bool = matches!(self, Contributor::StructuredName(_));
         let names = self.names(options.clone(), as_sorted);
+        self.format_names(names, options, locale)
+    }
+
+    /// Format the family names of the contributors.
+    pub fn format_short(&self, options: Config, locale: Locale) -> String {
+        self.format_names(self.family_names(), options, locale)
+    }
+
+    /// Format the names of the contributors as for citations, with the given names
+    /// in the given form, and with the whole list if `expand` is set.
+    pub fn format_cite_names(
+        &self,
+        options: Config,
+        locale: Locale,
+        given: GivenNameForm,
+        expand: bool,
+    ) -> String {
+        let mut options = options;
+        if expand {
+            let mut contributors = options.contributors.unwrap_or_default();
+            contributors.shorten = Some(ShortenListOptions {
+                min: u8::MAX,
+                ..contributors.shorten.unwrap_or_default()
+            });
+            options.contributors = Some(contributors);
+        }
+        self.format_names(self.cite_names(given), options, locale)
+    }
+
+    fn format_names(
+        &self,
+        names: Vec<String>,
+        options: Config,
+        locale: Locale,
+    ) -> String {
         let contributor_options = options.contributors.clone().unwrap_or_default();
         let shorten: bool =
             contributor_options.shorten.unwrap_or_default().min <= names.len() as u8;
@@ -821,3 +952,25 @@ fn display_and_sort_names() {
     let options = Config::default();
     assert_eq!(structured.names(options, true).join(", "), "Doe, John");
 }
+
+#[test]
+fn family_names() {
+    let contributor = Contributor::ContributorList(ContributorList(vec![
+        Contributor::StructuredName(StructuredName {
+            given: "Jane".to_string(),
+            family: "Doe".to_string(),
+        }),
+        Contributor::SimpleName(SimpleName { name: "ACME".to_string(), location: None }),
+    ]));
+    assert_eq!(contributor.family_names(), vec!["Doe", "ACME"]);
+}
+
+#[test]
+fn cite_names() {
+    let contributor = Contributor::StructuredName(StructuredName {
+        given: "Jane Mary".to_string(),
+        family: "Doe".to_string(),
+    });
+    assert_eq!(contributor.cite_names(GivenNameForm::Initials), vec!["J. M. Doe"]);
+    assert_eq!(contributor.cite_names(GivenNameForm::Full), vec!["Jane Mary Doe"]);
+}
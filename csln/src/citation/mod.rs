@@ -38,7 +38,7 @@ pub struct Citation {
     pub suffix: Option<String>,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub enum CitationModeType {
     /// Places the author inline in the text; also known as "narrative" or "in text" citations.
@@ -48,6 +48,23 @@ pub enum CitationModeType {
     NonIntegral,
 }
 
+/// The position of a citation item relative to the earlier citations of its reference.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Position {
+    /// The first citation of the reference.
+    #[default]
+    First,
+    /// A later citation of the reference.
+    Subsequent,
+    /// A citation of the same reference, and the same locators, as the one just
+    /// before it.
+    Ibid,
+    /// A citation of the same reference as the one just before it, with different
+    /// locators.
+    IbidWithLocator,
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CitationItem {
@@ -58,11 +75,17 @@ pub struct CitationItem {
     pub ref_id: String,
     /// An array of locator key-values and/or strings.
     pub suffix: Option<Vec<Locator>>,
+    /// Whether to omit the author; for example, "(2020)".
+    #[serde(default)]
+    pub suppress_author: bool,
+    /// Whether to render the author alone; for example, "Doe".
+    #[serde(default)]
+    pub author_only: bool,
 }
 
 #[allow(clippy::large_enum_variant)] // REVIEW is this a problem?
 /// A key-value object, or a string.
-#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(untagged)]
 pub enum Locator {
     KeyValue(LocatorKeyValue),
@@ -71,7 +94,7 @@ pub enum Locator {
 
 pub type LocatorKeyValue = (LocatorTerm, String);
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum LocatorTerm {
     Book,
@@ -6,9 +6,13 @@ use std::collections::HashMap;
 #[derive(Debug, Default, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct Locale {
     pub locale: String,
-    // pub options: LocaleOptions,
+    #[serde(default)]
+    pub options: LocaleOptions,
     pub dates: DateTerms,
     pub roles: HashMap<super::template::ContributorRole, ContributorTerm>,
+    /// The labels of locators, such as "p." and "pp." for pages.
+    #[serde(default)]
+    pub locators: HashMap<crate::citation::LocatorTerm, LocatorTermForms>,
     //pub contributors: ContributorTerms,
     pub terms: Terms, // TODO
 }
@@ -28,6 +32,12 @@ pub struct Terms {
     pub et_al: Option<String>,
     pub from: Option<String>,
     pub ibid: Option<String>,
+    pub loc_cit: Option<String>,
+    pub op_cit: Option<String>,
+    pub open_quote: Option<String>,
+    pub close_quote: Option<String>,
+    pub open_inner_quote: Option<String>,
+    pub close_inner_quote: Option<String>,
 }
 
 #[derive(Debug, Default, Deserialize, Serialize, Clone, JsonSchema)]
@@ -54,10 +64,24 @@ pub struct ContributorTerm {
     pub verb: SimpleTerm,
 }
 
+#[derive(Debug, Default, Deserialize, Serialize, Clone, JsonSchema)]
+pub struct LocatorTermForms {
+    /// The term for a single locator; for example, "page" or "p.".
+    pub singular: SimpleTerm,
+    /// The term for a range or list of locators; for example, "pages" or "pp.".
+    pub plural: SimpleTerm,
+}
+
 #[derive(Debug, Default, Deserialize, Serialize, Clone, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct LocaleOptions {
+    /// Whether periods and commas following a closing quotation mark move inside it.
+    #[serde(default)]
     pub punctuation_in_quotes: bool,
+    /// The leading words of titles to ignore when sorting by title; for example,
+    /// "the" and "a".
+    #[serde(default)]
+    pub sort_articles: Vec<String>,
 }
 
 /// A struct representing date terms.
@@ -264,3 +288,15 @@ pub enum LocalizedTermNameMisc {
 
     WorkingPaper,
 }
+
+#[test]
+fn locale_options_defaults() {
+    let options: LocaleOptions =
+        serde_yaml::from_str("sortArticles: [the, a]").expect("valid options");
+    assert!(!options.punctuation_in_quotes);
+    assert_eq!(options.sort_articles, vec!["the", "a"]);
+    let options: LocaleOptions =
+        serde_yaml::from_str("punctuationInQuotes: true").expect("valid options");
+    assert!(options.punctuation_in_quotes);
+    assert!(options.sort_articles.is_empty());
+}
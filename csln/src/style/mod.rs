@@ -3,6 +3,7 @@ SPDX-License-Identifier: MPL-2.0
 SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
 */
 
+use crate::citation::{CitationModeType, Position};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -12,7 +13,7 @@ pub mod options;
 use options::Config;
 
 pub mod template;
-use template::TemplateComponent;
+use template::{TemplateComponent, WrapPunctuation};
 
 /// The Style model.
 #[derive(Debug, Default, Deserialize, Serialize, Clone, JsonSchema)]
@@ -39,11 +40,125 @@ pub struct Bibliography {
     pub template: Template,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Debug, Default, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
 /// The citation specification.
 pub struct Citation {
     pub options: Option<Config>,
     pub template: Template,
+    /// The delimiter between the components of a citation item; by default a space.
+    pub delimiter: Option<String>,
+    /// The delimiter between the items of a citation; by default a semicolon.
+    pub item_delimiter: Option<String>,
+    /// The punctuation to wrap the citation in.
+    ///
+    /// Integral citations wrap each item after the author instead; for example,
+    /// "Doe (2020)".
+    pub wrap: Option<WrapPunctuation>,
+    /// Whether to collapse consecutive items by the same author, or consecutive
+    /// citation numbers.
+    pub collapse: Option<Collapse>,
+    /// The delimiter between collapsed items; by default a comma.
+    pub cite_group_delimiter: Option<String>,
+    /// The delimiter between items collapsed to their year suffixes; by default
+    /// the cite group delimiter. For example, with a semicolon as the cite group
+    /// delimiter and a comma here: "Doe 2020a, b; 2021".
+    pub year_suffix_delimiter: Option<String>,
+    /// The delimiter after a group of collapsed items; by default the item
+    /// delimiter.
+    pub after_collapse_delimiter: Option<String>,
+    /// How to sort the items of a citation; by default they are left in input
+    /// order.
+    pub sort: Option<options::Sort>,
+    /// Overrides for integral (narrative) citations.
+    pub integral: Option<CitationModeSpec>,
+    /// Overrides for non-integral citations.
+    pub non_integral: Option<CitationModeSpec>,
+    /// Overrides for later citations of a reference; for example, a short form
+    /// in a note style.
+    pub subsequent: Option<CitationModeSpec>,
+    /// Overrides for citations of the same reference as the one just before
+    /// them; by default those of subsequent citations.
+    pub ibid: Option<CitationModeSpec>,
+    /// The maximum number of notes between two citations of a reference for the
+    /// later one to be near the earlier; by default 5.
+    pub near_note_distance: Option<u32>,
+}
+
+impl Citation {
+    fn mode_spec(&self, mode: &CitationModeType) -> Option<&CitationModeSpec> {
+        match mode {
+            CitationModeType::Integral => self.integral.as_ref(),
+            CitationModeType::NonIntegral => self.non_integral.as_ref(),
+        }
+    }
+
+    /// Return the template for the citation mode and the position of the item.
+    pub fn template(&self, mode: &CitationModeType, position: &Position) -> &Template {
+        let position_specs = match position {
+            Position::First => vec![],
+            Position::Subsequent => vec![&self.subsequent],
+            Position::Ibid | Position::IbidWithLocator => {
+                vec![&self.ibid, &self.subsequent]
+            }
+        };
+        position_specs
+            .into_iter()
+            .flatten()
+            .chain(self.mode_spec(mode))
+            .find_map(|spec| spec.template.as_ref())
+            .unwrap_or(&self.template)
+    }
+
+    /// Return the maximum number of notes between two citations of a reference
+    /// for the later one to be near the earlier.
+    pub fn near_note_distance(&self) -> u32 {
+        self.near_note_distance.unwrap_or(5)
+    }
+
+    /// Return the delimiter between the components of a citation item.
+    pub fn delimiter(&self, mode: &CitationModeType) -> String {
+        self.mode_spec(mode)
+            .and_then(|spec| spec.delimiter.clone())
+            .or(self.delimiter.clone())
+            .unwrap_or(" ".to_string())
+    }
+
+    /// Return the delimiter between citation items.
+    pub fn item_delimiter(&self, mode: &CitationModeType) -> String {
+        let default = match mode {
+            CitationModeType::Integral => ", ",
+            CitationModeType::NonIntegral => "; ",
+        };
+        self.mode_spec(mode)
+            .and_then(|spec| spec.item_delimiter.clone())
+            .or(self.item_delimiter.clone())
+            .unwrap_or(default.to_string())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+/// How to collapse consecutive items by the same author in a citation.
+pub enum Collapse {
+    /// Render the author once; for example, "Doe 2020a, 2020b, 2021".
+    Year,
+    /// Also render the year once for items with year suffixes; for example,
+    /// "Doe 2020a, b, 2021".
+    YearSuffix,
+    /// Compress runs of three or more consecutive citation numbers into ranges;
+    /// for example, "[1–3, 7]".
+    CitationNumber,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+/// The parts of the citation specification specific to a citation mode; unset
+/// parts fall back to those of the citation specification.
+pub struct CitationModeSpec {
+    pub template: Option<Template>,
+    pub delimiter: Option<String>,
+    pub item_delimiter: Option<String>,
 }
 
 #[derive(Debug, Default, Deserialize, Serialize, Clone, JsonSchema)]
@@ -43,6 +43,8 @@ pub struct Config {
     pub contributors: Option<ContributorConfig>,
     pub dates: Option<Date>,
     pub titles: Option<TitlesConfig>,
+    pub links: Option<LinkOptions>,
+    pub locators: Option<LocatorOptions>,
 }
 
 #[derive(JsonSchema, Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
@@ -83,7 +85,11 @@ impl Processing {
                     ],
                 }),
                 group: Some(Group { template: vec![SortKey::Author, SortKey::Year] }),
-                disambiguate: Some(Disambiguation { names: true, year_suffix: true }),
+                disambiguate: Some(Disambiguation {
+                    names: true,
+                    year_suffix: true,
+                    given_name_rule: GivenNameRule::ByCite,
+                }),
             },
             Processing::Numeric => {
                 ProcessingCustom { sort: None, group: None, disambiguate: None }
@@ -105,16 +111,47 @@ fn author_date_config() {
 #[derive(JsonSchema, Debug, PartialEq, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Disambiguation {
+    /// Whether to tell apart the citations of different authors with the same
+    /// names; by showing more names of an et al list, then initials, then full
+    /// given names.
     pub names: bool,
     pub year_suffix: bool,
+    /// Which citations of an ambiguous name to add given names to.
+    #[serde(default)]
+    pub given_name_rule: GivenNameRule,
 }
 
 impl Default for Disambiguation {
     fn default() -> Self {
-        Self { names: true, year_suffix: false }
+        Self {
+            names: true,
+            year_suffix: false,
+            given_name_rule: GivenNameRule::default(),
+        }
     }
 }
 
+#[derive(JsonSchema, Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GivenNameRule {
+    /// Add given names only to the citations that would otherwise render the
+    /// same; for example, "J. Smith 2020" and "A. Smith 2020", but "Smith 2021".
+    #[default]
+    ByCite,
+    /// Add given names to every citation of a name shared by different people.
+    Global,
+}
+
+/// The form of the given names to add before family names in citations.
+#[derive(JsonSchema, Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GivenNameForm {
+    #[default]
+    None,
+    Initials,
+    Full,
+}
+
 #[derive(JsonSchema, Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Date {
     pub month: MonthFormat,
@@ -227,6 +264,54 @@ impl Default for ShortenListOptions {
     }
 }
 
+#[derive(JsonSchema, Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct LinkOptions {
+    /// How to render DOIs; they link to the DOI resolver in either form.
+    pub doi: DoiForm,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DoiForm {
+    /// The DOI resolver URL; for example, "https://doi.org/10.1000/182".
+    #[default]
+    Url,
+    /// The bare DOI; for example, "10.1000/182".
+    Bare,
+}
+
+#[test]
+fn link_options_default() {
+    let config = Config::default();
+    assert_eq!(config.links.unwrap_or_default().doi, DoiForm::Url);
+}
+
+#[derive(JsonSchema, Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct LocatorOptions {
+    /// When to render the localized label of a locator.
+    pub label: LocatorLabel,
+    /// The form of the label.
+    pub form: LocatorLabelForm,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LocatorLabel {
+    #[default]
+    Always,
+    /// Render labels, other than for pages; as in Chicago author-date.
+    Contextual,
+    Never,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone, JsonSchema, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LocatorLabelForm {
+    Long,
+    #[default]
+    Short,
+}
+
 #[derive(JsonSchema, Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Localize {
     pub scope: Scope,
@@ -319,7 +404,13 @@ pub enum SortKey {
     #[default]
     Author,
     Year,
+    /// The full issued date.
+    Issued,
     Title,
+    Type,
+    /// The citation number of the reference, for numeric styles.
+    #[serde(rename = "citation-number")]
+    CitationNumber,
 }
 
 #[derive(JsonSchema, Debug, PartialEq, Clone, Serialize, Deserialize)]
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 
 /// Rendering instructions for a template component.
 #[derive(Debug, Default, Deserialize, Serialize, Clone, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct Rendering {
     pub emph: Option<bool>,
     pub quote: Option<bool>,
@@ -10,6 +11,25 @@ pub struct Rendering {
     pub prefix: Option<String>,
     pub suffix: Option<String>,
     pub wrap: Option<WrapPunctuation>,
+    pub text_case: Option<TextCase>,
+}
+
+/// The case transform to apply to a template component.
+///
+/// Text marked up as `<span class="nocase">` in reference data is left as is.
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TextCase {
+    Lowercase,
+    Uppercase,
+    /// Capitalize the first word, if it is lowercase.
+    CapitalizeFirst,
+    /// Capitalize the first word, if it is lowercase; text in all uppercase is
+    /// lowercased first, other than text marked as nocase.
+    Sentence,
+    /// Capitalize every lowercase word, other than articles, conjunctions and
+    /// short prepositions after the first word or a colon.
+    Title,
 }
 
 /// The punctuation to wrap a template component in.
@@ -33,6 +53,7 @@ pub enum TemplateComponent {
     Title(TemplateTitle),
     Number(TemplateNumber),
     SimpleString(TemplateSimpleString),
+    Term(TemplateTerm),
 }
 
 impl TemplateComponent {
@@ -44,6 +65,7 @@ impl TemplateComponent {
             TemplateComponent::Title(t) => t.rendering.clone(),
             TemplateComponent::Number(n) => n.rendering.clone(),
             TemplateComponent::SimpleString(s) => s.rendering.clone(),
+            TemplateComponent::Term(t) => t.rendering.clone(),
         }
     }
 
@@ -54,6 +76,13 @@ impl TemplateComponent {
             _ => false,
         }
     }
+
+    pub fn is_citation_number(&self) -> bool {
+        match self {
+            TemplateComponent::Number(n) => n.number == Numbers::CitationNumber,
+            _ => false,
+        }
+    }
 }
 
 /// A simple string component, to render a string variable.
@@ -70,6 +99,25 @@ pub enum Variables {
     Doi,
     Isbn,
     Issn,
+    Url,
+}
+
+/// A term component, to render a localized term.
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema, PartialEq)]
+pub struct TemplateTerm {
+    pub term: GeneralTerm,
+    pub rendering: Option<Rendering>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GeneralTerm {
+    /// "ibid.", for a citation of the same reference as the one just before it.
+    Ibid,
+    /// "op. cit.", for a subsequent citation of a work.
+    OpCit,
+    /// "loc. cit.", for a subsequent citation of the same place in a work.
+    LocCit,
 }
 
 /// A number component, to render a number.
@@ -86,6 +134,9 @@ pub enum Numbers {
     Volume,
     Issue,
     Pages,
+    /// The number of the reference in a numeric style.
+    #[serde(rename = "citation-number")]
+    CitationNumber,
 }
 
 #[derive(Debug, Default, Deserialize, Serialize, Clone, JsonSchema, PartialEq)]
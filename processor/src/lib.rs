@@ -14,11 +14,16 @@ SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
 
 pub mod processor;
 pub mod render;
+pub mod session;
 pub mod types;
 pub mod values;
 
-pub use processor::Processor;
-pub use render::refs_to_string;
+pub use processor::{Processor, Selection};
+pub use render::{
+    refs_to_string, Djot, Html, Latex, Markdown, Odf, Ooxml, PlainText, Renderer, Rtf,
+    Typst,
+};
+pub use session::{Session, SessionUpdate};
 pub use types::{
     ProcBibliography, ProcCitation, ProcCitationItem, ProcCitations, ProcHints,
     ProcReferences, ProcTemplate, ProcTemplateComponent, ProcValues, RenderOptions,
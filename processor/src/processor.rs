@@ -3,22 +3,30 @@ SPDX-License-Identifier: MPL-2.0
 SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
 */
 
+use crate::render::strip_markup;
 use crate::types::{
     ProcBibliography, ProcCitation, ProcCitationItem, ProcCitations, ProcHints,
     ProcReferences, ProcTemplate, ProcTemplateComponent, ProcValues, RenderOptions,
 };
-use crate::values::ComponentValues;
-use csln::bibliography::reference::{InputReference, RefID};
+use crate::values::{format_locator, ComponentValues};
+use csln::bibliography::reference::{Contributor, InputReference, RefID};
 use csln::bibliography::InputBibliography as Bibliography;
-use csln::citation::{Citation, CitationItem, Citations};
+use csln::citation::{
+    Citation, CitationItem, CitationModeType, Citations, Locator, Position,
+};
 use csln::style::locale::Locale;
-use csln::style::options::{Config, SortKey, SubstituteKey};
+use csln::style::options::{
+    Config, GivenNameForm, GivenNameRule, Sort, SortKey, SubstituteKey,
+};
 use csln::style::template::TemplateComponent;
-use csln::style::Style;
+use csln::style::{Collapse, Style};
+use icu::collator::{Collator, CollatorOptions};
 use itertools::Itertools;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
 
 /// The processor struct, which takes a style, a bibliography, and a locale, and renders the output.
 #[derive(Debug, Default, Deserialize, Serialize)]
@@ -26,11 +34,44 @@ pub struct Processor {
     /// The input style.
     style: Style,
     /// The input bibliography.
-    bibliography: Bibliography,
+    pub(crate) bibliography: Bibliography,
     /// The input citations.
-    citations: Citations,
+    pub(crate) citations: Citations,
     /// The output locale.
     locale: Locale,
+    /// The references to include in the bibliography.
+    #[serde(default)]
+    selection: Selection,
+    /// The collator for the output locale, built on first use.
+    #[serde(skip)]
+    collator: OnceLock<LocaleCollator>,
+}
+
+/// The references to include in the bibliography.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Selection {
+    /// The cited references.
+    #[default]
+    Cited,
+    /// The cited references, and those with the listed ids.
+    Nocite(Vec<String>),
+    /// All the references in the input bibliography.
+    All,
+}
+
+impl Selection {
+    /// Return the selection for a list of ids to include without citing them,
+    /// where "*" includes all the references.
+    pub fn with_nocite(nocite: Vec<String>) -> Selection {
+        if nocite.iter().any(|id| id == "*") {
+            Selection::All
+        } else if nocite.is_empty() {
+            Selection::Cited
+        } else {
+            Selection::Nocite(nocite)
+        }
+    }
 }
 
 impl Processor {
@@ -45,71 +86,208 @@ impl Processor {
             bibliography,
             citations,
             locale,
+            selection: Selection::default(),
+            collator: OnceLock::new(),
         }
     }
 
+    /// Set the references to include in the bibliography.
+    pub fn with_selection(mut self, selection: Selection) -> Processor {
+        self.selection = selection;
+        self
+    }
+
     /// Render references to AST.
     #[inline]
     pub fn process_references(&self) -> ProcReferences {
-        let sorted_references = self.sort_references(self.get_references());
-        let bibliography: ProcBibliography = sorted_references
+        let hints = self.get_proc_hints();
+        let bibliography: ProcBibliography = self
+            .get_bibliography_references()
             .par_iter()
-            .map(|reference| self.process_reference(reference))
+            .map(|reference| self.process_reference(reference, &hints))
             .collect();
         let citations = if self.citations.is_empty() {
             None
         } else {
-            Some(self.process_citations(&self.citations))
+            Some(self.process_citations(&self.citations, &hints))
         };
         ProcReferences { bibliography, citations }
     }
 
-    fn process_citations(&self, citations: &Citations) -> ProcCitations {
+    /// Return the selected references, in bibliography order.
+    pub(crate) fn get_bibliography_references(&self) -> Vec<InputReference> {
+        let mut sorted_references = self.sort_references(self.get_selected_references());
+        if self.get_sort().is_none() {
+            // Numeric styles without a sort list the references by citation number.
+            let citation_numbers = self.get_citation_numbers();
+            sorted_references.sort_by_key(|reference| {
+                citation_numbers.get(&reference.id().unwrap_or_default()).copied()
+            });
+        }
+        sorted_references
+    }
+
+    fn process_citations(
+        &self,
+        citations: &Citations,
+        hints: &HashMap<String, ProcHints>,
+    ) -> ProcCitations {
         citations
             .iter()
-            .map(|citation| self.process_citation(citation))
+            .zip(self.get_citation_positions(citations))
+            .map(|(citation, positions)| {
+                self.process_citation(citation, &positions, hints)
+            })
             .collect()
     }
 
-    fn process_citation(&self, citation: &Citation) -> ProcCitation {
-        // TODO handle the prefix and suffix, though am uncertain how to best do that
-        let pcitation = citation
-            .citation_items
-            .iter()
-            .filter_map(|citation_item| self.process_citation_item(citation_item))
-            .collect();
-        println!("pcitation: {:?}", pcitation);
-        pcitation
+    /// Return the position of each item of the citations, and whether the previous
+    /// citation of its reference is near.
+    pub(crate) fn get_citation_positions(
+        &self,
+        citations: &Citations,
+    ) -> Vec<Vec<(Position, bool)>> {
+        let near_note_distance = self
+            .style
+            .citation
+            .as_ref()
+            .map(|style| style.near_note_distance())
+            .unwrap_or(5);
+        citation_positions(citations, near_note_distance)
+    }
+
+    pub(crate) fn process_citation(
+        &self,
+        citation: &Citation,
+        positions: &[(Position, bool)],
+        hints: &HashMap<String, ProcHints>,
+    ) -> ProcCitation {
+        let citation_style = self.style.citation.as_ref();
+        let mut citation_items: Vec<_> =
+            citation.citation_items.iter().zip(positions).collect();
+        if let Some(sort) = citation_style.and_then(|style| style.sort.as_ref()) {
+            self.sort_citation_items(&mut citation_items, sort, hints);
+        }
+        let mut items = citation_items
+            .into_iter()
+            .filter_map(|(citation_item, (position, near_note))| {
+                let item = self.process_citation_item_in_mode(
+                    citation_item,
+                    &citation.mode,
+                    position,
+                    hints,
+                );
+                item.map(|item| ProcCitationItem { near_note: *near_note, ..item })
+            })
+            .collect::<Vec<ProcCitationItem>>();
+        if let Some(style) = citation_style {
+            if let (Some(collapse), CitationModeType::NonIntegral) =
+                (&style.collapse, &citation.mode)
+            {
+                if collapse == &Collapse::CitationNumber {
+                    collapse_citation_numbers(&mut items);
+                } else {
+                    collapse_items(&mut items, collapse, style);
+                }
+            }
+        }
+        ProcCitation {
+            prefix: citation.prefix.clone(),
+            items,
+            suffix: citation.suffix.clone(),
+            mode: citation.mode.clone(),
+            delimiter: citation_style
+                .map(|style| style.item_delimiter(&citation.mode))
+                .unwrap_or("; ".to_string()),
+            wrap: citation_style
+                .and_then(|style| style.wrap.clone())
+                .unwrap_or_default(),
+        }
     }
 
+    /// Process a citation item, as for the first, non-integral citation of its
+    /// reference.
     pub fn process_citation_item(
         &self,
         citation_item: &CitationItem,
     ) -> Option<ProcCitationItem> {
-        let citation_style = self.style.citation.clone();
-        // FIXME below is returning None
-        let reference = match self.get_reference(&citation_item.ref_id) {
-            Ok(reference) => reference,
-            Err(_) => return None, // or handle the error in a different way
-        };
-        let proc_template =
-            self.process_template(&reference, citation_style?.template.as_slice());
-        println!("proc_template: {:?}", proc_template);
-        Some(proc_template)
+        self.process_citation_item_in_mode(
+            citation_item,
+            &CitationModeType::NonIntegral,
+            &Position::First,
+            &self.get_proc_hints(),
+        )
+    }
+
+    fn process_citation_item_in_mode(
+        &self,
+        citation_item: &CitationItem,
+        mode: &CitationModeType,
+        position: &Position,
+        hints: &HashMap<String, ProcHints>,
+    ) -> Option<ProcCitationItem> {
+        let citation_style = self.style.citation.as_ref()?;
+        let reference = self.get_reference(&citation_item.ref_id).ok()?;
+        let template = citation_style.template(mode, position);
+        let mut template = self.process_template(&reference, template, hints);
+        if citation_item.author_only {
+            template.retain(|component| component.template_component.is_author());
+        } else if citation_item.suppress_author {
+            template.retain(|component| !component.template_component.is_author());
+        }
+        // Citation options take precedence over the style options.
+        let locator_options = citation_style
+            .options
+            .as_ref()
+            .and_then(|options| options.locators.clone())
+            .or(self.render_options().global.locators)
+            .unwrap_or_default();
+        let locators: Vec<String> = citation_item
+            .suffix
+            .iter()
+            .flatten()
+            .map(|locator| match locator {
+                Locator::KeyValue((term, value)) => {
+                    format_locator(term, value, &self.locale, &locator_options)
+                }
+                Locator::String(value) => value.clone(),
+            })
+            .collect();
+        // A plain ibid cites the same locators as the item before it.
+        let omit_locators = citation_item.author_only || position == &Position::Ibid;
+        Some(ProcCitationItem {
+            prefix: citation_item.prefix.clone(),
+            template,
+            suffix: if locators.is_empty() || omit_locators {
+                None
+            } else {
+                Some(locators.join(", "))
+            },
+            delimiter: citation_style.delimiter(mode),
+            group_delimiter: None,
+            position: *position,
+            near_note: false,
+        })
     }
 
     /// Render a reference to AST.
-    fn process_reference(
+    pub(crate) fn process_reference(
         &self,
         reference: &InputReference,
+        hints: &HashMap<String, ProcHints>,
     ) -> Vec<ProcTemplateComponent> {
         if let Some(bibliography_style) = &self.style.bibliography {
-             self.process_template(reference, &bibliography_style.template)
+             self.process_template(reference, &bibliography_style.template, hints)
         } else {
              Vec::new()
         }
     }
 
+    /// Return the options to render the processed references with.
+    pub fn render_options(&self) -> RenderOptions {
+        self.get_render_options(self.style.clone(), self.locale.clone())
+    }
+
     fn get_render_options(&self, style: Style, locale: Locale) -> RenderOptions {
         RenderOptions {
             global: style.options.unwrap_or_default(),
@@ -122,25 +300,38 @@ impl Processor {
         &self,
         reference: &InputReference,
         template: &[TemplateComponent],
+        hints: &HashMap<String, ProcHints>,
     ) -> ProcTemplate {
-        template
+        let reference_id: Option<RefID> = reference.id();
+        let hint: ProcHints =
+            // TODO why would reference_id be None?
+            hints.get(&reference_id.unwrap_or_default()).cloned().unwrap_or_default();
+        let mut template: ProcTemplate = template
             .iter()
-            .filter_map(|component| self.process_template_component(component, reference))
-            .collect()
+            .filter_map(|component| {
+                self.process_template_component(component, reference, &hint)
+            })
+            .collect();
+        // A reference has its year suffix only once, after the first year.
+        template
+            .iter_mut()
+            .filter(|component| {
+                matches!(component.template_component, TemplateComponent::Date(_))
+                    && component.values.suffix.as_ref().is_some_and(|s| !s.is_empty())
+            })
+            .skip(1)
+            .for_each(|component| component.values.suffix = None);
+        template
     }
 
     fn process_template_component(
         &self,
         component: &TemplateComponent,
         reference: &InputReference,
+        hint: &ProcHints,
     ) -> Option<ProcTemplateComponent> {
-        let hints = self.get_proc_hints();
-        let reference_id: Option<RefID> = reference.id();
-        let hint: ProcHints =
-            // TODO why would reference_id be None?
-            hints.get(&reference_id.unwrap_or_default()).cloned().unwrap_or_default();
         let options = self.get_render_options(self.style.clone(), self.locale.clone());
-        let values = component.values(reference, &hint, &options)?;
+        let values = component.values(reference, hint, &options)?;
         let template_component = component.clone();
         // TODO add role here if specified in the style
         // TODO affixes from style?
@@ -151,6 +342,7 @@ impl Processor {
                     value: values.value,
                     prefix: values.prefix,
                     suffix: values.suffix,
+                    url: values.url,
                 },
             })
         } else {
@@ -194,11 +386,33 @@ impl Processor {
     /// Get a reference from the bibliography by id/citekey.
     pub fn get_reference(&self, id: &str) -> Result<InputReference, String> {
         match self.bibliography.get(id) {
-            Some(reference) => Ok(reference.clone()),
+            Some(reference) => {
+                let mut reference = reference.clone();
+                reference.set_id(id.to_string());
+                Ok(reference)
+            }
             None => Err(format!("Invalid reference ID: {}", id)),
         }
     }
 
+    /// Return the references to include in the bibliography.
+    pub fn get_selected_references(&self) -> Vec<InputReference> {
+        let references = self.get_references();
+        let keys: HashSet<String> = match &self.selection {
+            Selection::All => return references,
+            Selection::Cited => self.get_cited_keys().into_iter().collect(),
+            Selection::Nocite(nocite) => self
+                .get_cited_keys()
+                .into_iter()
+                .chain(nocite.iter().cloned())
+                .collect(),
+        };
+        references
+            .into_iter()
+            .filter(|reference| reference.id().is_some_and(|id| keys.contains(&id)))
+            .collect()
+    }
+
     pub fn get_cited_references(&self) -> Vec<InputReference> {
         let mut cited_references = Vec::new();
         for key in &self.get_cited_keys() {
@@ -222,6 +436,48 @@ impl Processor {
             .collect()
     }
 
+    /// Return the sort instructions in the style, if any.
+    fn get_sort(&self) -> Option<Sort> {
+        let options: Config = self.style.options.clone().unwrap_or_default();
+        options.processing.unwrap_or_default().config().sort
+    }
+
+    /// Return the citation number of each reference.
+    ///
+    /// References are numbered in bibliography order when the style sorts the
+    /// bibliography, and otherwise in order of first citation, followed by any
+    /// references that are not cited.
+    pub fn get_citation_numbers(&self) -> HashMap<String, usize> {
+        if self.get_sort().is_none() {
+            return self.get_citation_order();
+        }
+        let references = self.sort_references(self.get_selected_references());
+        references
+            .iter()
+            .filter_map(|reference| reference.id())
+            .enumerate()
+            .map(|(index, key)| (key, index + 1))
+            .collect()
+    }
+
+    /// Number the selected references in order of first citation, followed by
+    /// any references that are not cited, in key order.
+    fn get_citation_order(&self) -> HashMap<String, usize> {
+        let mut keys: Vec<String> =
+            self.get_selected_references().iter().filter_map(|r| r.id()).collect();
+        keys.sort();
+        let cited_keys = self
+            .get_cited_keys()
+            .into_iter()
+            .filter(|key| self.bibliography.contains_key(key));
+        let mut citation_numbers = HashMap::new();
+        for key in cited_keys.chain(keys) {
+            let next = citation_numbers.len() + 1;
+            citation_numbers.entry(key).or_insert(next);
+        }
+        citation_numbers
+    }
+
     /// Sort the references according to instructions in the style.
     #[inline]
     pub fn sort_references(
@@ -230,53 +486,182 @@ impl Processor {
     ) -> Vec<InputReference> {
         let mut references: Vec<InputReference> = references;
         let options: Config = self.style.options.clone().unwrap_or_default();
-        if let Some(sort_config) =
-            options.processing.clone().unwrap_or_default().config().sort
+        let collator = self.get_collator();
+        let sort_config = self.get_sort().unwrap_or_default();
+        let citation_numbers = if sort_config
+            .template
+            .iter()
+            .any(|spec| spec.key == SortKey::CitationNumber)
         {
-            sort_config.template.iter().rev().for_each(|sort| match sort.key {
-                SortKey::Author => {
-                    references.par_sort_by(|a, b| {
-                        let a_author = match a.author() {
-                            Some(author) => author.names(options.clone(), true).join("-"),
-                            None => match self.get_author_substitute(a) {
-                                Some((substitute, _)) => substitute,
-                                None => "".to_string(),
-                            },
-                        };
+            self.get_citation_order()
+        } else {
+            HashMap::new()
+        };
+        // References that sort the same are ordered by title, as for year suffixes.
+        references.sort_by(|a, b| {
+            let numbers = &citation_numbers;
+            self.compare_references(a, b, &sort_config, &options, numbers, collator)
+                .then_with(|| self.compare_titles(a, b, collator))
+        });
+        references
+    }
 
-                        let b_author = match b.author() {
-                            Some(author) => author.names(options.clone(), true).join("-"),
-                            None => match self.get_author_substitute(b) {
-                                Some((substitute, _)) => substitute,
-                                None => "".to_string(),
-                            },
-                        };
-                        a_author.to_lowercase().cmp(&b_author.to_lowercase())
-                    });
-                }
-                SortKey::Year => {
-                    references.par_sort_by(|a: &InputReference, b: &InputReference| {
-                        let a_year = a.issued().as_ref().map(|d| d.year()).unwrap_or_default();
-                        let b_year = b.issued().as_ref().map(|d| d.year()).unwrap_or_default();
-                        b_year.cmp(&a_year)
-                    });
+    /// Return the collator for the output locale; or for the root locale, if the
+    /// output locale is not a valid language tag.
+    fn get_collator(&self) -> &LocaleCollator {
+        self.collator.get_or_init(|| {
+            let locale: icu::locid::Locale =
+                self.locale.locale.parse().unwrap_or(icu::locid::Locale::UND);
+            LocaleCollator(Collator::try_new(&locale.into(), CollatorOptions::new()).ok())
+        })
+    }
+
+    /// Compare two references by title, and then by id.
+    fn compare_titles(
+        &self,
+        a: &InputReference,
+        b: &InputReference,
+        collator: &LocaleCollator,
+    ) -> Ordering {
+        collator
+            .compare(&self.title_sort_key(a), &self.title_sort_key(b))
+            .then_with(|| a.id().cmp(&b.id()))
+    }
+
+    /// Return the title to sort a reference by, without any leading article of
+    /// the locale.
+    fn title_sort_key(&self, reference: &InputReference) -> String {
+        let title = reference
+            .title()
+            .map(|title| strip_markup(&title.to_string()))
+            .unwrap_or_default();
+        let articles = &self.locale.options.sort_articles;
+        match title.split_once(' ') {
+            Some((first, rest))
+                if articles
+                    .iter()
+                    .any(|article| article.to_lowercase() == first.to_lowercase()) =>
+            {
+                rest.to_string()
+            }
+            _ => title,
+        }
+    }
+
+    /// Compare two references by each key of the sort in turn, in the direction
+    /// of the key; names and titles are compared by the collation of the locale.
+    fn compare_references(
+        &self,
+        a: &InputReference,
+        b: &InputReference,
+        sort: &Sort,
+        options: &Config,
+        citation_numbers: &HashMap<String, usize>,
+        collator: &LocaleCollator,
+    ) -> Ordering {
+        let year = |reference: &InputReference| {
+            reference
+                .issued()
+                .as_ref()
+                .map(|date| date.year())
+                .unwrap_or_default()
+        };
+        let issued = |reference: &InputReference| {
+            reference
+                .issued()
+                .as_ref()
+                .map(|date| date.sort_key())
+                .unwrap_or_default()
+        };
+        let number = |reference: &InputReference| {
+            reference.id().and_then(|id| citation_numbers.get(&id).copied())
+        };
+        sort.template
+            .iter()
+            .map(|spec| {
+                let ordering = match spec.key {
+                    SortKey::Author => collator.compare(
+                        &self.author_sort_key(a, options),
+                        &self.author_sort_key(b, options),
+                    ),
+                    SortKey::Year => year(a).cmp(&year(b)),
+                    SortKey::Issued => issued(a).cmp(&issued(b)),
+                    SortKey::Title => {
+                        collator.compare(&self.title_sort_key(a), &self.title_sort_key(b))
+                    }
+                    SortKey::Type => a.ref_type().cmp(&b.ref_type()),
+                    SortKey::CitationNumber => number(a).cmp(&number(b)),
+                    _ => Ordering::Equal,
+                };
+                if spec.ascending {
+                    ordering
+                } else {
+                    ordering.reverse()
                 }
-                _ => {}
-            });
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+
+    /// Return the string to sort a reference by author with.
+    fn author_sort_key(&self, reference: &InputReference, options: &Config) -> String {
+        match reference.author() {
+            Some(author) => author.names(options.clone(), true).join("-"),
+            None => match self.get_author_substitute(reference) {
+                Some((substitute, _)) => substitute,
+                None => "".to_string(),
+            },
         }
-        references
+    }
+
+    /// Sort the items of a citation according to the citation sort instructions.
+    ///
+    /// Items of references that are not in the bibliography sort the same as any
+    /// other item.
+    fn sort_citation_items<T>(
+        &self,
+        items: &mut [(&CitationItem, T)],
+        sort: &Sort,
+        hints: &HashMap<String, ProcHints>,
+    ) {
+        let options: Config = self.style.options.clone().unwrap_or_default();
+        let citation_numbers: HashMap<String, usize> = items
+            .iter()
+            .filter_map(|(item, _)| {
+                let citation_number = hints.get(&item.ref_id)?.citation_number?;
+                Some((item.ref_id.clone(), citation_number))
+            })
+            .collect();
+        let collator = self.get_collator();
+        items.sort_by(|(a, _), (b, _)| {
+            match (self.get_reference(&a.ref_id), self.get_reference(&b.ref_id)) {
+                (Ok(a), Ok(b)) => {
+                    let numbers = &citation_numbers;
+                    self.compare_references(&a, &b, sort, &options, numbers, collator)
+                }
+                _ => Ordering::Equal,
+            }
+        });
     }
 
     /// Process the references and return a HashMap of ProcHints.
     pub fn get_proc_hints(&self) -> HashMap<String, ProcHints> {
-        let refs = self.get_references();
+        let refs = self.get_selected_references();
         let sorted_refs = self.sort_references(refs);
-        let grouped_refs = self.group_references(sorted_refs);
-        let proc_hints = grouped_refs
+        // Names are disambiguated first, so that year suffixes only tell apart the
+        // citations that would still render the same.
+        let name_hints = &self.disambiguate_names(&sorted_refs);
+        let grouped_refs = self.group_references(sorted_refs, name_hints);
+        let citation_numbers = &self.get_citation_numbers();
+        let collator = self.get_collator();
+        let proc_hints: HashMap<String, ProcHints> = grouped_refs
             .iter()
             .flat_map(|(key, group)| {
                 let group_len = group.len();
-                group.iter().enumerate().filter_map(
+                // Year suffixes follow the order of the titles within the group.
+                let mut group: Vec<&InputReference> = group.iter().collect();
+                group.sort_by(|a, b| self.compare_titles(a, b, collator));
+                group.into_iter().enumerate().filter_map(
                     move |(index, reference)| -> Option<(String, ProcHints)> {
                         // TODO will need to generalize.
                         let disambiguate = group_len > 1;
@@ -285,6 +670,7 @@ impl Processor {
                             group_index: index + 1,
                             group_length: group_len,
                             group_key: key.clone(),
+                            ..Default::default()
                         };
                         let ref_id = match reference {
                             InputReference::Monograph(monograph) => monograph.id.clone(),
@@ -298,7 +684,17 @@ impl Processor {
                                 collection.id.clone()
                             }
                         };
-                        ref_id.map(|id| (id, proc_hint))
+                        ref_id.map(|id| {
+                            let citation_number = citation_numbers.get(&id).copied();
+                            let names = name_hints.get(&id).cloned().unwrap_or_default();
+                            let proc_hint = ProcHints {
+                                citation_number,
+                                given_names: names.given_names,
+                                expand_names: names.expand_names,
+                                ..proc_hint
+                            };
+                            (id, proc_hint)
+                        })
                     },
                 )
             })
@@ -306,23 +702,102 @@ impl Processor {
         proc_hints
     }
 
+    /// Return the hints with the forms of the author names that tell apart the
+    /// citations of different authors, which would otherwise render the same.
+    ///
+    /// The cascade tries, in order: all the names of a shortened list, initials,
+    /// and full given names. Citations of the same authors are left to year
+    /// suffixes.
+    fn disambiguate_names(
+        &self,
+        references: &[InputReference],
+    ) -> HashMap<String, ProcHints> {
+        let mut name_hints = HashMap::new();
+        let options = self.render_options();
+        let processing = options.global.processing.clone().unwrap_or_default();
+        let disambiguation = processing.config().disambiguate.unwrap_or_default();
+        if !disambiguation.names {
+            return name_hints;
+        }
+        const LEVELS: [(GivenNameForm, bool); 6] = [
+            (GivenNameForm::None, false),
+            (GivenNameForm::None, true),
+            (GivenNameForm::Initials, false),
+            (GivenNameForm::Initials, true),
+            (GivenNameForm::Full, false),
+            (GivenNameForm::Full, true),
+        ];
+        let format = |author: &Contributor, (given, expand): (GivenNameForm, bool)| {
+            let locale = options.locale.clone();
+            author.format_cite_names(options.global.clone(), locale, given, expand)
+        };
+        // The references by the citation they would otherwise render.
+        let mut groups: HashMap<(String, String), Vec<(String, Contributor)>> =
+            HashMap::new();
+        for reference in references {
+            let (Some(id), Some(author)) = (reference.id(), reference.author()) else {
+                continue;
+            };
+            let year = match disambiguation.given_name_rule {
+                GivenNameRule::ByCite => {
+                    reference.issued().as_ref().map(|d| d.year()).unwrap_or_default()
+                }
+                GivenNameRule::Global => Default::default(),
+            };
+            let key = (format(&author, LEVELS[0]), year.to_string());
+            groups.entry(key).or_default().push((id, author));
+        }
+        for group in groups.values().filter(|group| group.len() > 1) {
+            let forms: Vec<Vec<String>> = group
+                .iter()
+                .map(|(_, author)| LEVELS.map(|level| format(author, level)).to_vec())
+                .collect();
+            for ((id, _), names) in group.iter().zip(&forms) {
+                // The full names identify the authors.
+                let full = LEVELS.len() - 1;
+                let others: Vec<&Vec<String>> =
+                    forms.iter().filter(|other| other[full] != names[full]).collect();
+                if others.is_empty() {
+                    continue;
+                }
+                let distinct = |level: &usize| {
+                    others.iter().all(|other| other[*level] != names[*level])
+                };
+                let level = (1..LEVELS.len()).find(distinct);
+                if let Some(level) = level {
+                    let (given_names, expand_names) = LEVELS[level];
+                    name_hints.insert(
+                        id.clone(),
+                        ProcHints { given_names, expand_names, ..Default::default() },
+                    );
+                }
+            }
+        }
+        name_hints
+    }
+
     /// Return a string to use for grouping for a given reference, using instructions in the style.
-    fn make_group_key(&self, reference: &InputReference) -> String {
+    ///
+    /// Authors are keyed by the names the citation renders, in the form given by
+    /// the hint.
+    fn make_group_key(&self, reference: &InputReference, hint: &ProcHints) -> String {
         let options: Config = match self.style.options {
             Some(ref options) => options.clone(),
             None => Config::default(), // TODO is this right?
         };
         let group_template = options.processing.unwrap_or_default().config().group.as_ref().map(|g| g.template.clone()).unwrap_or_default();
         let options = self.style.options.clone();
-        let as_sorted = false;
         let group_key = group_template
             // This is likely unnecessary, but just in case.
             .par_iter()
             .map(|key| match key {
                 SortKey::Author => match reference.author() {
-                    Some(author) => {
-                        author.names(options.clone().unwrap_or_default(), as_sorted).join("-")
-                    }
+                    Some(author) => author.format_cite_names(
+                        options.clone().unwrap_or_default(),
+                        self.locale.clone(),
+                        hint.given_names,
+                        hint.expand_names,
+                    ),
                     None => "".to_string(),
                 },
                 SortKey::Year => {
@@ -356,18 +831,234 @@ impl Processor {
             })
     }
 
-    /// Group references according to instructions in the style.
+    /// Group references according to instructions in the style, and the name forms
+    /// of their hints.
     #[inline]
     pub fn group_references(
         &self,
         references: Vec<InputReference>,
+        hints: &HashMap<String, ProcHints>,
     ) -> HashMap<String, Vec<InputReference>> {
         references
             .into_iter()
-            .group_by(|reference| self.make_group_key(reference))
-            .into_iter()
-            .map(|(key, group)| (key, group.collect()))
-            .collect()
+            .map(|reference| {
+                let hint = reference.id().and_then(|id| hints.get(&id).cloned());
+                (self.make_group_key(&reference, &hint.unwrap_or_default()), reference)
+            })
+            .into_group_map()
+    }
+}
+
+/// Compares strings by the collation of a locale; or by code point, if there is no
+/// collation data for it.
+#[derive(Debug)]
+struct LocaleCollator(Option<Collator>);
+
+impl LocaleCollator {
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        match &self.0 {
+            Some(collator) => collator.compare(a, b),
+            None => a.cmp(b),
+        }
+    }
+}
+
+/// Return the position of each item of the citations, and whether the previous
+/// citation of its reference is within the near-note distance.
+///
+/// An item is an ibid when the item just before it, in the same citation or as
+/// the only item of the previous citation, cites the same reference; unless that
+/// item has locators and this one does not.
+fn citation_positions(
+    citations: &Citations,
+    near_note_distance: u32,
+) -> Vec<Vec<(Position, bool)>> {
+    let mut last_notes: HashMap<&str, Option<i32>> = HashMap::new();
+    let mut previous: Option<&CitationItem> = None;
+    let mut positions = Vec::with_capacity(citations.len());
+    for citation in citations {
+        let mut citation_positions = Vec::with_capacity(citation.citation_items.len());
+        for item in &citation.citation_items {
+            let last_note = last_notes.get(item.ref_id.as_str());
+            let position = match (last_note, previous) {
+                (None, _) => Position::First,
+                (Some(_), Some(prev)) if prev.ref_id == item.ref_id => {
+                    match (&prev.suffix, &item.suffix) {
+                        (None, None) => Position::Ibid,
+                        (None, Some(_)) => Position::IbidWithLocator,
+                        (Some(_), None) => Position::Subsequent,
+                        (Some(prev_locators), Some(locators)) => {
+                            if prev_locators == locators {
+                                Position::Ibid
+                            } else {
+                                Position::IbidWithLocator
+                            }
+                        }
+                    }
+                }
+                (Some(_), _) => Position::Subsequent,
+            };
+            let near_note = match (last_note.copied().flatten(), citation.note_number) {
+                (Some(last), Some(note)) => {
+                    note >= last && (note - last) as u32 <= near_note_distance
+                }
+                _ => false,
+            };
+            citation_positions.push((position, near_note));
+            last_notes.insert(item.ref_id.as_str(), citation.note_number);
+            previous = Some(item);
+        }
+        if citation.citation_items.len() > 1 {
+            // Only a citation of a single reference can precede an ibid.
+            previous = None;
+        }
+        positions.push(citation_positions);
+    }
+    positions
+}
+
+/// Collapse consecutive citation items by the same author, rendering the
+/// author only for the first of them.
+///
+/// Items with a prefix or locators break a run of collapsed items.
+fn collapse_items(
+    items: &mut [ProcCitationItem],
+    collapse: &Collapse,
+    style: &csln::style::Citation,
+) {
+    let cite_group_delimiter =
+        style.cite_group_delimiter.clone().unwrap_or(", ".to_string());
+    let year_suffix_delimiter = style
+        .year_suffix_delimiter
+        .clone()
+        .unwrap_or(cite_group_delimiter.clone());
+    let has_affixes =
+        |item: &ProcCitationItem| item.prefix.is_some() || item.suffix.is_some();
+    let authors: Vec<String> = items
+        .iter()
+        .map(|item| {
+            item.template
+                .iter()
+                .filter(|component| component.template_component.is_author())
+                .map(|component| component.values.value.clone())
+                .collect::<Vec<String>>()
+                .join(" ")
+        })
+        .collect();
+    let dates: Vec<Option<String>> = items
+        .iter()
+        .map(|item| {
+            item.template
+                .iter()
+                .find(|component| {
+                    matches!(component.template_component, TemplateComponent::Date(_))
+                })
+                .map(|component| component.values.value.clone())
+        })
+        .collect();
+    // Whether each item is collapsed into the one before it.
+    let mut collapsed = vec![false; items.len()];
+    for index in 1..items.len() {
+        if authors[index].is_empty()
+            || authors[index] != authors[index - 1]
+            || has_affixes(&items[index - 1])
+            || has_affixes(&items[index])
+        {
+            if collapsed[index - 1] {
+                items[index].group_delimiter = style.after_collapse_delimiter.clone();
+            }
+            continue;
+        }
+        collapsed[index] = true;
+        let same_year = dates[index].is_some() && dates[index] == dates[index - 1];
+        let item = &mut items[index];
+        item.template
+            .retain(|component| !component.template_component.is_author());
+        let mut delimiter = &cite_group_delimiter;
+        if collapse == &Collapse::YearSuffix && same_year {
+            // Render the year suffix alone.
+            for component in item.template.iter_mut() {
+                if matches!(component.template_component, TemplateComponent::Date(_))
+                    && component.values.suffix.as_ref().is_some_and(|s| !s.is_empty())
+                {
+                    component.values.value = String::new();
+                    delimiter = &year_suffix_delimiter;
+                }
+            }
+        }
+        item.group_delimiter = Some(delimiter.clone());
+    }
+}
+
+/// Compress runs of three or more consecutive citation numbers into a range,
+/// rendered by the first item of the run.
+///
+/// Only items that render the citation number alone, without a prefix or
+/// locators, take part in ranges.
+fn collapse_citation_numbers(items: &mut Vec<ProcCitationItem>) {
+    let numbers: Vec<Option<usize>> = items
+        .iter()
+        .map(|item| match item.template.as_slice() {
+            [component]
+                if component.template_component.is_citation_number()
+                    && item.prefix.is_none()
+                    && item.suffix.is_none() =>
+            {
+                component.values.value.parse().ok()
+            }
+            _ => None,
+        })
+        .collect();
+    let mut collapsed = Vec::with_capacity(items.len());
+    let mut index = 0;
+    for (start, mut item) in std::mem::take(items).into_iter().enumerate() {
+        if start < index {
+            continue;
+        }
+        index = start + 1;
+        if let Some(first) = numbers[start] {
+            while numbers.get(index).copied().flatten() == Some(first + index - start) {
+                index += 1;
+            }
+            if index - start >= 3 {
+                let last = first + index - start - 1;
+                item.template[0].values.value = format!("{}–{}", first, last);
+            } else {
+                index = start + 1;
+            }
+        }
+        collapsed.push(item);
+    }
+    *items = collapsed;
+}
+
+/// Return a style that cites the short author names and the year of issue, with the
+/// other citation options of `citation`, for processor and session tests.
+#[cfg(test)]
+pub(crate) fn author_year_style(
+    citation: csln::style::Citation,
+    options: Option<Config>,
+) -> Style {
+    use csln::style::template::{
+        ContributorForm, ContributorRole, DateForm, Dates, TemplateContributor,
+        TemplateDate,
+    };
+    let template = vec![
+        TemplateComponent::Contributor(TemplateContributor {
+            contributor: ContributorRole::Author,
+            form: ContributorForm::Short,
+            rendering: None,
+        }),
+        TemplateComponent::Date(TemplateDate {
+            date: Dates::Issued,
+            form: DateForm::Year,
+            rendering: None,
+        }),
+    ];
+    Style {
+        options,
+        citation: Some(csln::style::Citation { template, ..citation }),
+        ..Default::default()
     }
 }
 
@@ -375,7 +1066,6 @@ impl Processor {
 mod tests {
     use super::*;
     use csln::bibliography::reference::{Monograph, StructuredName, Contributor, EdtfString, Title};
-    use csln::style::options::SortKey;
 
     fn mock_reference(id: &str, family: &str, year: &str) -> InputReference {
         let name = StructuredName {
@@ -404,9 +1094,576 @@ mod tests {
         // Test default grouping (should be empty or based on default config)
         let processor = Processor::default();
         let reference = mock_reference("ref1", "Smith", "2020");
-        let key = processor.make_group_key(&reference);
-        // Default group key produces "First Last:Year" format or similar depending on implementation
-        // The failure shows "Given Smith:2020"
-        assert_eq!(key, "Given Smith:2020");
+        let key = processor.make_group_key(&reference, &ProcHints::default());
+        // The default group key is the short author names, as cited, and the year.
+        assert_eq!(key, "Smith:2020");
+    }
+
+    #[test]
+    fn collapses_items_by_the_same_author() {
+        use crate::render::{PlainText, Renderer};
+        use csln::style::template::WrapPunctuation;
+        let style_with = |collapse: Collapse| csln::style::Citation {
+            wrap: Some(WrapPunctuation::Parentheses),
+            collapse: Some(collapse),
+            ..Default::default()
+        };
+        let bibliography: Bibliography = [
+            ("doe1", "Doe", "2020"),
+            ("doe2", "Doe", "2020"),
+            ("doe3", "Doe", "2021"),
+            ("roe1", "Roe", "2019"),
+        ]
+        .iter()
+        .map(|(id, family, year)| (id.to_string(), mock_reference(id, family, year)))
+        .collect();
+        let render = |citation_style: csln::style::Citation, locator: Option<&str>| {
+            let style = author_year_style(citation_style, None);
+            let mut processor = Processor::new(
+                style,
+                bibliography.clone(),
+                Vec::new(),
+                Locale::default(),
+            )
+            .with_selection(Selection::All);
+            // Cite the items with year suffixes in the order of their suffixes.
+            let hints = processor.get_proc_hints();
+            let mut ids = ["doe1", "doe2"];
+            ids.sort_by_key(|id| hints[*id].group_index);
+            let citation = Citation {
+                citation_items: [ids[0], ids[1], "doe3", "roe1"]
+                    .iter()
+                    .map(|id| CitationItem {
+                        ref_id: id.to_string(),
+                        suffix: match (*id, locator) {
+                            ("doe3", Some(locator)) => {
+                                Some(vec![Locator::String(locator.to_string())])
+                            }
+                            _ => None,
+                        },
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            };
+            processor.citations = vec![citation];
+            let citations = processor.process_references().citations.unwrap_or_default();
+            PlainText.render_citation(&citations[0], &processor.render_options())
+        };
+        let year = style_with(Collapse::Year);
+        assert_eq!(render(year.clone(), None), "(Doe 2020a, 2020b, 2021; Roe 2019)");
+        let year_suffix = style_with(Collapse::YearSuffix);
+        assert_eq!(render(year_suffix.clone(), None), "(Doe 2020a, b, 2021; Roe 2019)");
+        let year_suffix_delimiter = csln::style::Citation {
+            cite_group_delimiter: Some("; ".to_string()),
+            year_suffix_delimiter: Some(", ".to_string()),
+            ..year_suffix.clone()
+        };
+        assert_eq!(render(year_suffix_delimiter, None), "(Doe 2020a, b; 2021; Roe 2019)");
+        let after_collapse_delimiter = csln::style::Citation {
+            item_delimiter: Some(", ".to_string()),
+            after_collapse_delimiter: Some("; ".to_string()),
+            ..year
+        };
+        assert_eq!(
+            render(after_collapse_delimiter, None),
+            "(Doe 2020a, 2020b, 2021; Roe 2019)"
+        );
+        // An item with locators is not collapsed.
+        assert_eq!(
+            render(year_suffix, Some("p. 5")),
+            "(Doe 2020a, b; Doe 2021, p. 5; Roe 2019)"
+        );
+    }
+
+    #[test]
+    fn disambiguates_names() {
+        use crate::render::{PlainText, Renderer};
+        use csln::bibliography::reference::ContributorList;
+        use csln::style::options::{
+            AndOptions, ContributorConfig, Disambiguation, Processing, ProcessingCustom,
+        };
+        let with_author = |id: &str, names: &[(&str, &str)], year: &str| {
+            let mut reference = mock_reference(id, "", year);
+            let names = names
+                .iter()
+                .map(|(given, family)| {
+                    Contributor::StructuredName(StructuredName {
+                        given: given.to_string(),
+                        family: family.to_string(),
+                    })
+                })
+                .collect();
+            if let InputReference::Monograph(monograph) = &mut reference {
+                let names = ContributorList(names);
+                monograph.author = Some(Contributor::ContributorList(names));
+            }
+            (id.to_string(), reference)
+        };
+        let bibliography: Bibliography = [
+            with_author("jane", &[("Jane", "Smith")], "2020"),
+            with_author("adam", &[("Adam", "Smith")], "2020"),
+            with_author("alan", &[("Alan", "Smith")], "2020"),
+            with_author("jane2", &[("Jane", "Smith")], "2021"),
+            with_author("jane3", &[("Jane", "Smith")], "2021"),
+            with_author(
+                "roe",
+                &[("A", "Doe"), ("B", "Roe"), ("C", "Poe"), ("D", "Moe"), ("E", "Roe")],
+                "2019",
+            ),
+            with_author(
+                "zoe",
+                &[("A", "Doe"), ("B", "Roe"), ("C", "Poe"), ("D", "Moe"), ("E", "Zoe")],
+                "2019",
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let style_with = |names: bool, given_name_rule: GivenNameRule| {
+            let options = Config {
+                processing: Some(Processing::Custom(ProcessingCustom {
+                    sort: Processing::AuthorDate.config().sort,
+                    group: Processing::AuthorDate.config().group,
+                    disambiguate: Some(Disambiguation {
+                        names,
+                        year_suffix: true,
+                        given_name_rule,
+                    }),
+                })),
+                contributors: Some(ContributorConfig {
+                    and: Some(AndOptions::Text),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+            author_year_style(csln::style::Citation::default(), Some(options))
+        };
+        let render = |names: bool, given_name_rule: GivenNameRule| {
+            let citations = ["jane", "adam", "alan", "jane2", "jane3", "roe", "zoe"]
+                .iter()
+                .map(|id| Citation {
+                    citation_items: vec![CitationItem {
+                        ref_id: id.to_string(),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                })
+                .collect();
+            let processor = Processor::new(
+                style_with(names, given_name_rule),
+                bibliography.clone(),
+                citations,
+                Locale::default(),
+            );
+            let options = processor.render_options();
+            let citations = processor.process_references().citations.unwrap_or_default();
+            citations
+                .iter()
+                .map(|citation| PlainText.render_citation(citation, &options))
+                .collect::<Vec<String>>()
+        };
+        let rendered = render(true, GivenNameRule::ByCite);
+        assert_eq!(
+            rendered[..3],
+            ["J. Smith 2020", "Adam Smith 2020", "Alan Smith 2020"]
+        );
+        // The same author is left to year suffixes.
+        assert!(rendered[3].starts_with("Smith 2021") && rendered[3] != rendered[4]);
+        assert_eq!(rendered[5], "Doe, Roe, Poe, Moe, and Roe 2019");
+        assert_eq!(rendered[6], "Doe, Roe, Poe, Moe, and Zoe 2019");
+        let rendered = render(true, GivenNameRule::Global);
+        assert!(rendered[3].starts_with("J. Smith 2021"));
+        // Without name disambiguation, different authors who share a family name
+        // render the same, and so are told apart by year suffixes.
+        let rendered = render(false, GivenNameRule::ByCite);
+        assert_eq!(rendered[..3], ["Smith 2020c", "Smith 2020a", "Smith 2020b"]);
+    }
+
+    #[test]
+    fn sorts_citation_items() {
+        use crate::render::{PlainText, Renderer};
+        use csln::style::options::SortSpec;
+        use csln::style::template::WrapPunctuation;
+        let sort_by = |template: Vec<SortSpec>| Sort {
+            shorten_names: false,
+            render_substitutions: false,
+            template,
+        };
+        let style_with = |sort: Sort| {
+            let citation = csln::style::Citation {
+                wrap: Some(WrapPunctuation::Parentheses),
+                collapse: Some(Collapse::Year),
+                sort: Some(sort),
+                ..Default::default()
+            };
+            author_year_style(citation, None)
+        };
+        let bibliography: Bibliography =
+            [("doe1", "Doe", "2021"), ("doe2", "Doe", "2019"), ("roe1", "Roe", "2020")]
+                .iter()
+                .map(|(id, family, year)| {
+                    (id.to_string(), mock_reference(id, family, year))
+                })
+                .collect();
+        let citation = Citation {
+            citation_items: ["roe1", "doe1", "doe2"]
+                .iter()
+                .map(|id| CitationItem { ref_id: id.to_string(), ..Default::default() })
+                .collect(),
+            ..Default::default()
+        };
+        let render = |sort: Sort| {
+            let processor = Processor::new(
+                style_with(sort),
+                bibliography.clone(),
+                vec![citation.clone()],
+                Locale::default(),
+            );
+            let citations = processor.process_references().citations.unwrap_or_default();
+            PlainText.render_citation(&citations[0], &processor.render_options())
+        };
+        let author_year = sort_by(vec![
+            SortSpec { key: SortKey::Author, ascending: true },
+            SortSpec { key: SortKey::Year, ascending: true },
+        ]);
+        assert_eq!(render(author_year), "(Doe 2019, 2021; Roe 2020)");
+        let year_descending =
+            sort_by(vec![SortSpec { key: SortKey::Year, ascending: false }]);
+        assert_eq!(render(year_descending), "(Doe 2021; Roe 2020; Doe 2019)");
+        let citation_number =
+            sort_by(vec![SortSpec { key: SortKey::CitationNumber, ascending: true }]);
+        // Citation numbers follow the bibliography order of the style.
+        assert_eq!(render(citation_number), "(Doe 2019, 2021; Roe 2020)");
+    }
+
+    #[test]
+    fn sorts_references_by_keys() {
+        use csln::bibliography::reference::MonographType;
+        use csln::style::options::{Processing, ProcessingCustom, SortSpec};
+        let reference = |id: &str, date: &str, title: &str, r#type: MonographType| {
+            let mut reference = mock_reference(id, "Doe", date);
+            if let InputReference::Monograph(monograph) = &mut reference {
+                monograph.title = Title::Single(title.to_string());
+                monograph.r#type = r#type;
+            }
+            reference
+        };
+        let references = vec![
+            reference("a", "2020-05", "Beta", MonographType::Book),
+            reference("b", "2020-01-15", "alpha", MonographType::Report),
+            reference("c", "2019", "Gamma", MonographType::Book),
+        ];
+        let sorted = |template: Vec<SortSpec>| {
+            let style = Style {
+                options: Some(Config {
+                    processing: Some(Processing::Custom(ProcessingCustom {
+                        sort: Some(Sort {
+                            shorten_names: false,
+                            render_substitutions: false,
+                            template,
+                        }),
+                        group: None,
+                        disambiguate: None,
+                    })),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+            let processor = Processor { style, ..Default::default() };
+            processor
+                .sort_references(references.clone())
+                .iter()
+                .filter_map(|reference| reference.id())
+                .collect::<Vec<String>>()
+        };
+        let spec = |key: SortKey, ascending: bool| SortSpec { key, ascending };
+        assert_eq!(sorted(vec![spec(SortKey::Issued, true)]), ["c", "b", "a"]);
+        assert_eq!(sorted(vec![spec(SortKey::Issued, false)]), ["a", "b", "c"]);
+        assert_eq!(sorted(vec![spec(SortKey::Title, true)]), ["b", "a", "c"]);
+        assert_eq!(
+            sorted(vec![spec(SortKey::Type, false), spec(SortKey::Year, false)]),
+            ["b", "a", "c"]
+        );
+        assert_eq!(
+            sorted(vec![spec(SortKey::Author, true), spec(SortKey::Year, true)]),
+            ["c", "b", "a"]
+        );
+    }
+
+    #[test]
+    fn collates_by_locale() {
+        use csln::style::options::{Processing, ProcessingCustom, SortSpec};
+        let references: Vec<InputReference> = [
+            ("zhou", "Zhou", "<i>The Zebra</i>"),
+            ("angstrom", "Ångström", "A Yak"),
+            ("celik", "Çelik", "Apple"),
+            ("adams", "Adams", "an Xylophone"),
+        ]
+        .iter()
+        .map(|(id, family, title)| {
+            let mut reference = mock_reference(id, family, "2020");
+            if let InputReference::Monograph(monograph) = &mut reference {
+                monograph.title = Title::Single(title.to_string());
+            }
+            reference
+        })
+        .collect();
+        let sorted = |language: &str, key: SortKey| {
+            let mut locale =
+                Locale { locale: language.to_string(), ..Default::default() };
+            locale.options.sort_articles = vec!["the".to_string(), "a".to_string()];
+            let sort = Sort {
+                shorten_names: false,
+                render_substitutions: false,
+                template: vec![SortSpec { key, ascending: true }],
+            };
+            let style = Style {
+                options: Some(Config {
+                    processing: Some(Processing::Custom(ProcessingCustom {
+                        sort: Some(sort),
+                        ..Default::default()
+                    })),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+            let processor = Processor { style, locale, ..Default::default() };
+            processor
+                .sort_references(references.clone())
+                .iter()
+                .filter_map(|reference| reference.id())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(sorted("en", SortKey::Author), ["adams", "angstrom", "celik", "zhou"]);
+        // Swedish sorts "Å" after "Z".
+        assert_eq!(sorted("sv", SortKey::Author), ["adams", "celik", "zhou", "angstrom"]);
+        // Only the articles of the locale are ignored, after any markup.
+        assert_eq!(sorted("en", SortKey::Title), ["adams", "celik", "angstrom", "zhou"]);
+    }
+
+    #[test]
+    fn adds_year_suffix_once() {
+        use crate::render::{PlainText, Renderer};
+        use csln::style::template::{
+            DateForm, Dates, TemplateDate, TemplateTitle, Titles,
+        };
+        let date = |form: DateForm| {
+            TemplateComponent::Date(TemplateDate {
+                date: Dates::Issued,
+                form,
+                rendering: None,
+            })
+        };
+        let template = vec![
+            date(DateForm::Year),
+            TemplateComponent::Title(TemplateTitle {
+                title: Titles::Primary,
+                form: None,
+                rendering: None,
+            }),
+            date(DateForm::MonthDay),
+        ];
+        let style = Style {
+            bibliography: Some(csln::style::Bibliography { options: None, template }),
+            ..Default::default()
+        };
+        let bibliography: Bibliography = [("doe1", "2020-03-01"), ("doe2", "2020-05-01")]
+            .iter()
+            .map(|(id, date)| (id.to_string(), mock_reference(id, "Doe", date)))
+            .collect();
+        let mut locale = Locale::default();
+        locale.dates.months.long = ["January", "February", "March", "April", "May"]
+            .iter()
+            .map(|month| month.to_string())
+            .collect();
+        let processor = Processor::new(style, bibliography, Vec::new(), locale)
+            .with_selection(Selection::All);
+        let options = processor.render_options();
+        let entries: Vec<String> = processor
+            .process_references()
+            .bibliography
+            .iter()
+            .map(|entry| PlainText.render_template(entry, &options))
+            .collect();
+        assert_eq!(entries, ["2020a. Title. March 1.", "2020b. Title. May 1."]);
+    }
+
+    #[test]
+    fn numbers_references_in_order_of_first_citation() {
+        use crate::render::{PlainText, Renderer};
+        use csln::style::options::Processing;
+        use csln::style::template::{
+            ContributorForm, ContributorRole, Numbers, Rendering, TemplateContributor,
+            TemplateNumber, WrapPunctuation,
+        };
+        let citation_number = TemplateComponent::Number(TemplateNumber {
+            number: Numbers::CitationNumber,
+            form: None,
+            rendering: Some(Rendering {
+                wrap: Some(WrapPunctuation::Brackets),
+                ..Default::default()
+            }),
+        });
+        let style = Style {
+            options: Some(Config {
+                processing: Some(Processing::Numeric),
+                ..Default::default()
+            }),
+            citation: Some(csln::style::Citation {
+                template: vec![TemplateComponent::Number(TemplateNumber {
+                    number: Numbers::CitationNumber,
+                    form: None,
+                    rendering: None,
+                })],
+                item_delimiter: Some(", ".to_string()),
+                wrap: Some(WrapPunctuation::Brackets),
+                collapse: Some(Collapse::CitationNumber),
+                ..Default::default()
+            }),
+            bibliography: Some(csln::style::Bibliography {
+                options: None,
+                template: vec![
+                    citation_number,
+                    TemplateComponent::Contributor(TemplateContributor {
+                        contributor: ContributorRole::Author,
+                        form: ContributorForm::Short,
+                        rendering: None,
+                    }),
+                ],
+            }),
+            ..Default::default()
+        };
+        let bibliography: Bibliography = ["Adams", "Brown", "Clark", "Doe", "Evans"]
+            .iter()
+            .map(|family| (family.to_string(), mock_reference(family, family, "2020")))
+            .collect();
+        let cite = |ids: &[&str]| Citation {
+            citation_items: ids
+                .iter()
+                .map(|id| CitationItem { ref_id: id.to_string(), ..Default::default() })
+                .collect(),
+            ..Default::default()
+        };
+        let citations = vec![
+            cite(&["Doe"]),
+            cite(&["Adams", "Brown", "Clark", "Doe"]),
+            cite(&["Doe", "Adams", "Brown"]),
+        ];
+        // Include the uncited reference, numbered after the cited ones.
+        let processor = Processor::new(style, bibliography, citations, Locale::default())
+            .with_selection(Selection::All);
+        let references = processor.process_references();
+        let options = processor.render_options();
+        let rendered: Vec<String> = references
+            .citations
+            .unwrap_or_default()
+            .iter()
+            .map(|citation| PlainText.render_citation(citation, &options))
+            .collect();
+        assert_eq!(rendered, vec!["[1]", "[2–4, 1]", "[1–3]"]);
+        assert_eq!(
+            PlainText.render_template(&references.bibliography[0], &options),
+            "[1] Doe."
+        );
+        assert_eq!(
+            PlainText.render_template(&references.bibliography[4], &options),
+            "[5] Evans."
+        );
+    }
+
+    #[test]
+    fn tracks_citation_positions() {
+        use crate::render::{PlainText, Renderer};
+        use csln::citation::LocatorTerm;
+        use csln::style::template::{
+            ContributorForm, ContributorRole, GeneralTerm, Rendering,
+            TemplateContributor, TemplateTerm, TemplateTitle, TextCase, Titles,
+        };
+        use csln::style::CitationModeSpec;
+        let author = TemplateComponent::Contributor(TemplateContributor {
+            contributor: ContributorRole::Author,
+            form: ContributorForm::Short,
+            rendering: None,
+        });
+        let title = TemplateComponent::Title(TemplateTitle {
+            title: Titles::Primary,
+            form: None,
+            rendering: Some(Rendering { emph: Some(true), ..Default::default() }),
+        });
+        let ibid = TemplateComponent::Term(TemplateTerm {
+            term: GeneralTerm::Ibid,
+            rendering: Some(Rendering {
+                text_case: Some(TextCase::CapitalizeFirst),
+                ..Default::default()
+            }),
+        });
+        let spec = |template: Vec<TemplateComponent>| CitationModeSpec {
+            template: Some(template),
+            ..Default::default()
+        };
+        let style = Style {
+            citation: Some(csln::style::Citation {
+                template: vec![author.clone(), title],
+                delimiter: Some(", ".to_string()),
+                subsequent: Some(spec(vec![author])),
+                ibid: Some(spec(vec![ibid])),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let bibliography: Bibliography = ["Doe", "Roe"]
+            .iter()
+            .map(|family| (family.to_string(), mock_reference(family, family, "2020")))
+            .collect();
+        let cite = |note: i32, ids: &[(&str, Option<&str>)]| Citation {
+            note_number: Some(note),
+            citation_items: ids
+                .iter()
+                .map(|(id, page)| CitationItem {
+                    ref_id: id.to_string(),
+                    suffix: page.map(|page| {
+                        vec![Locator::KeyValue((LocatorTerm::Page, page.to_string()))]
+                    }),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let citations = vec![
+            cite(1, &[("Doe", Some("10"))]),
+            cite(2, &[("Doe", Some("10"))]),
+            cite(3, &[("Doe", Some("12"))]),
+            cite(4, &[("Doe", None)]),
+            cite(5, &[("Roe", None), ("Doe", None)]),
+            cite(12, &[("Doe", None)]),
+        ];
+        let mut locale = Locale::default();
+        locale.terms.ibid = Some("ibid.".to_string());
+        let processor = Processor::new(style, bibliography, citations, locale);
+        let citations = processor.process_references().citations.unwrap_or_default();
+        let positions: Vec<Vec<(Position, bool)>> = citations
+            .iter()
+            .map(|c| c.items.iter().map(|item| (item.position, item.near_note)).collect())
+            .collect();
+        assert_eq!(
+            positions,
+            vec![
+                vec![(Position::First, false)],
+                vec![(Position::Ibid, true)],
+                vec![(Position::IbidWithLocator, true)],
+                vec![(Position::Subsequent, true)],
+                vec![(Position::First, false), (Position::Subsequent, true)],
+                vec![(Position::Subsequent, false)],
+            ]
+        );
+        let options = processor.render_options();
+        let rendered: Vec<String> = citations
+            .iter()
+            .map(|citation| PlainText.render_citation(citation, &options))
+            .collect();
+        assert_eq!(
+            rendered,
+            vec!["Doe, Title, 10", "Ibid.", "Ibid., 12", "Doe", "Roe, Title; Doe", "Doe"]
+        );
     }
 }
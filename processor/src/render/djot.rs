@@ -0,0 +1,96 @@
+/*
+SPDX-License-Identifier: MPL-2.0
+SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
+*/
+
+use super::{encode_url, mark_roman, wrap_runs, Renderer};
+
+/// Renders [Djot](https://djot.net) markup.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Djot;
+
+impl Renderer for Djot {
+    fn text(&self, text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            if matches!(
+                c,
+                '\\' | '*' | '_' | '`' | '[' | ']' | '{' | '}' | '~' | '^' | '<' | '>'
+            ) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    }
+
+    fn emph(&self, content: String) -> String {
+        wrap_runs(&content, "_", "_", true)
+    }
+
+    fn strong(&self, content: String) -> String {
+        wrap_runs(&content, "*", "*", false)
+    }
+
+    fn roman(&self, content: String) -> String {
+        mark_roman(content)
+    }
+
+    fn link(&self, content: String, url: &str) -> String {
+        format!("[{}]({})", content, encode_url(url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::title;
+    use crate::types::RenderOptions;
+    use csln::style::template::Rendering;
+
+    #[test]
+    fn renders_formatting() {
+        let component = title(
+            "A *starred* title",
+            Rendering {
+                emph: Some(true),
+                quote: Some(true),
+                strong: Some(true),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            Djot.render_component(&component, &RenderOptions::default()),
+            "“*_A \\*starred\\* title_*”"
+        );
+    }
+
+    #[test]
+    fn keeps_whitespace_outside_delimiters() {
+        assert_eq!(Djot.emph(" Title ".to_string()), " _Title_ ");
+        assert_eq!(Djot.strong("Title ".to_string()), "*Title* ");
+    }
+
+    #[test]
+    fn renders_roman_within_emphasis() {
+        use crate::render::Inline;
+        let text = |text: &str| Inline::Text(text.to_string());
+        let inlines = [Inline::Emph(vec![
+            text("On "),
+            Inline::Emph(vec![text("Homo sapiens")]),
+            text(" in Titles"),
+        ])];
+        assert_eq!(
+            Djot.render_inlines(&inlines, &RenderOptions::default()),
+            "_On_ Homo sapiens _in Titles_"
+        );
+        let inlines = [Inline::Emph(vec![Inline::Strong(vec![
+            text("On "),
+            Inline::Emph(vec![text("Homo")]),
+        ])])];
+        assert_eq!(
+            Djot.render_inlines(&inlines, &RenderOptions::default()),
+            "_*On*_ *Homo*"
+        );
+    }
+}
@@ -0,0 +1,128 @@
+/*
+SPDX-License-Identifier: MPL-2.0
+SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
+*/
+
+use super::{escape_xml, Renderer};
+
+/// Renders HTML fragments.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Html;
+
+impl Renderer for Html {
+    fn text(&self, text: &str) -> String {
+        escape_xml(text)
+    }
+
+    fn emph(&self, content: String) -> String {
+        format!("<i>{}</i>", content)
+    }
+
+    fn strong(&self, content: String) -> String {
+        format!("<b>{}</b>", content)
+    }
+
+    fn roman(&self, content: String) -> String {
+        format!("<span style=\"font-style:normal\">{}</span>", content)
+    }
+
+    fn link(&self, content: String, url: &str) -> String {
+        format!("<a href=\"{}\">{}</a>", escape_xml(url), content)
+    }
+
+    fn entry(&self, content: String) -> String {
+        format!("  <div class=\"csl-entry\">{}</div>", content)
+    }
+
+    fn bibliography(&self, entries: Vec<String>) -> String {
+        format!("<div class=\"csl-bib-body\">\n{}\n</div>", entries.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::title;
+    use crate::types::RenderOptions;
+    use csln::style::template::{Rendering, TextCase};
+
+    #[test]
+    fn renders_formatting() {
+        let mut options = RenderOptions::default();
+        let component = title(
+            "Title",
+            Rendering {
+                emph: Some(true),
+                strong: Some(true),
+                ..Default::default()
+            },
+        );
+        assert_eq!(Html.render_component(&component, &options), "<b><i>Title</i></b>");
+        options.locale.terms.open_quote = Some("«".to_string());
+        options.locale.terms.close_quote = Some("»".to_string());
+        let component =
+            title("Title", Rendering { quote: Some(true), ..Default::default() });
+        assert_eq!(Html.render_component(&component, &options), "«Title»");
+    }
+
+    #[test]
+    fn renders_nested_quotes() {
+        let mut options = RenderOptions::default();
+        options.locale.options.punctuation_in_quotes = true;
+        let quoted = Rendering { quote: Some(true), ..Default::default() };
+        let template = vec![
+            title("On \"Quotes\" in Titles", quoted.clone()),
+            title("Another", quoted),
+        ];
+        assert_eq!(
+            Html.render_template(&template, &options),
+            "“On ‘Quotes’ in Titles.” “Another.”"
+        );
+        options.locale.options.punctuation_in_quotes = false;
+        assert_eq!(
+            Html.render_template(&template, &options),
+            "“On ‘Quotes’ in Titles”. “Another”."
+        );
+    }
+
+    #[test]
+    fn renders_title_markup() {
+        let component = title(
+            "the <i>Homo sapiens</i> of <span class=\"nocase\">iOS</span>",
+            Rendering {
+                emph: Some(true),
+                text_case: Some(TextCase::Title),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            Html.render_component(&component, &RenderOptions::default()),
+            "<i>The <span style=\"font-style:normal\">Homo Sapiens</span> of iOS</i>"
+        );
+    }
+
+    #[test]
+    fn renders_links() {
+        let mut component = title("https://example.com/?a=1&b=2", Rendering::default());
+        component.values.url = Some(component.values.value.clone());
+        assert_eq!(
+            Html.render_component(&component, &RenderOptions::default()),
+            "<a href=\"https://example.com/?a=1&amp;b=2\">https://example.com/?a=1&amp;b=2</a>"
+        );
+    }
+
+    #[test]
+    fn escapes_reference_data() {
+        let component = title(
+            "Q&A <with> \"quotes\"",
+            Rendering {
+                prefix: Some("In ".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            Html.render_component(&component, &RenderOptions::default()),
+            "In Q&amp;A &lt;with&gt; &quot;quotes&quot;"
+        );
+    }
+}
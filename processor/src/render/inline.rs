@@ -0,0 +1,502 @@
+/*
+SPDX-License-Identifier: MPL-2.0
+SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
+*/
+
+//! Format-independent rich text, which renderers turn into their output format.
+//!
+//! Processed components are first converted to [`Inline`] content, so that
+//! adjustments that depend on the surrounding content, like alternating nested
+//! quotes, happen in one place for all output formats.
+
+use super::markup::{change_case, parse_markup};
+use super::wrap_punctuation;
+use crate::types::{
+    ProcCitation, ProcCitationItem, ProcTemplateComponent, RenderOptions,
+};
+use csln::citation::CitationModeType;
+use csln::style::locale::Locale;
+use csln::style::template::{TemplateComponent, WrapPunctuation};
+
+/// A piece of rich text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inline {
+    /// Plain text.
+    Text(String),
+    /// Emphasized content.
+    Emph(Vec<Inline>),
+    /// Strong content.
+    Strong(Vec<Inline>),
+    /// Quoted content; quotes nested within quotes use the inner quotation marks.
+    Quoted(Vec<Inline>),
+    /// Content linked to a URL.
+    Link { url: String, content: Vec<Inline> },
+    /// Content protected from case transforms.
+    NoCase(Vec<Inline>),
+}
+
+/// Return the opening and closing quotation marks of the locale.
+pub fn quote_marks(locale: &Locale, inner: bool) -> (&str, &str) {
+    let terms = &locale.terms;
+    if inner {
+        (
+            terms.open_inner_quote.as_deref().unwrap_or("‘"),
+            terms.close_inner_quote.as_deref().unwrap_or("’"),
+        )
+    } else {
+        (
+            terms.open_quote.as_deref().unwrap_or("“"),
+            terms.close_quote.as_deref().unwrap_or("”"),
+        )
+    }
+}
+
+/// Return the rich text for a component, including its affixes and wrapping.
+pub fn component_inlines(
+    component: &ProcTemplateComponent,
+    options: &RenderOptions,
+) -> Vec<Inline> {
+    let rendering = component.template_component.rendering().unwrap_or_default();
+    let (open, close) = wrap_punctuation(&rendering.wrap.unwrap_or_default());
+    let values = &component.values;
+    let value = values.prefix.clone().unwrap_or_default()
+        + &values.value
+        + values.suffix.as_deref().unwrap_or_default();
+    let mut content = match component.template_component {
+        TemplateComponent::Title(_) => parse_markup(&value),
+        _ => vec![Inline::Text(value)],
+    };
+    let quote = rendering.quote.unwrap_or_default();
+    if quote {
+        let (open_quote, close_quote) = quote_marks(&options.locale, false);
+        content = map_text(content, &|text| parse_quotes(&text, open_quote, close_quote));
+    }
+    if let Some(case) = &rendering.text_case {
+        change_case(&mut content, case);
+    }
+    if let Some(url) = &values.url {
+        content = vec![Inline::Link { url: url.clone(), content }];
+    }
+    if rendering.emph.unwrap_or_default() {
+        content = vec![Inline::Emph(content)];
+    }
+    if rendering.strong.unwrap_or_default() {
+        content = vec![Inline::Strong(content)];
+    }
+    if quote {
+        content = vec![Inline::Quoted(content)];
+    }
+    let mut inlines = vec![
+        Inline::Text(open.to_string()),
+        Inline::Text(rendering.prefix.unwrap_or_default()),
+    ];
+    inlines.extend(content);
+    append_text(&mut inlines, &rendering.suffix.unwrap_or_default());
+    append_text(&mut inlines, close);
+    inlines.retain(|inline| inline != &Inline::Text(String::new()));
+    inlines
+}
+
+/// Return the rich text for a citation item, including its prefix and locators.
+pub fn citation_item_inlines(
+    item: &ProcCitationItem,
+    options: &RenderOptions,
+) -> Vec<Inline> {
+    let mut inlines = Vec::new();
+    if let Some(prefix) = &item.prefix {
+        inlines.push(Inline::Text(prefix.clone()));
+    }
+    inlines.extend(item_content_inlines(&item.template, item, options));
+    inlines
+}
+
+/// Return the rich text for an item of an integral citation, with the author
+/// outside of the wrapping; for example, "Doe (2020, 42)".
+fn integral_item_inlines(
+    item: &ProcCitationItem,
+    wrap: &WrapPunctuation,
+    options: &RenderOptions,
+) -> Vec<Inline> {
+    let (authors, rest): (Vec<_>, Vec<_>) = item
+        .template
+        .iter()
+        .cloned()
+        .partition(|component| component.template_component.is_author());
+    let authors = authors
+        .iter()
+        .map(|component| component_inlines(component, options))
+        .collect();
+    let mut content = item_content_inlines(&rest, item, options);
+    if !content.is_empty() {
+        let (open, close) = wrap_punctuation(wrap);
+        content.insert(0, Inline::Text(open.to_string()));
+        content.push(Inline::Text(close.to_string()));
+        content.retain(|inline| inline != &Inline::Text(String::new()));
+    }
+    let mut inlines = Vec::new();
+    if let Some(prefix) = &item.prefix {
+        inlines.push(Inline::Text(prefix.clone()));
+    }
+    inlines.extend(join(vec![join(authors, &item.delimiter), content], " "));
+    inlines
+}
+
+/// Return the rich text for the components of a citation item, followed by its
+/// locators.
+fn item_content_inlines(
+    components: &[ProcTemplateComponent],
+    item: &ProcCitationItem,
+    options: &RenderOptions,
+) -> Vec<Inline> {
+    let parts = components
+        .iter()
+        .map(|component| component_inlines(component, options))
+        .collect();
+    let locators = item.suffix.iter().map(|suffix| vec![Inline::Text(suffix.clone())]);
+    join(
+        std::iter::once(join(parts, &item.delimiter))
+            .chain(locators)
+            .collect(),
+        ", ",
+    )
+}
+
+/// Return the rich text for a citation, including its affixes and wrapping.
+pub fn citation_inlines(citation: &ProcCitation, options: &RenderOptions) -> Vec<Inline> {
+    let (open, close) = match citation.mode {
+        CitationModeType::Integral => ("", ""),
+        CitationModeType::NonIntegral => wrap_punctuation(&citation.wrap),
+    };
+    let mut items = Vec::new();
+    for item in &citation.items {
+        let part = match citation.mode {
+            CitationModeType::Integral => {
+                integral_item_inlines(item, &citation.wrap, options)
+            }
+            CitationModeType::NonIntegral => citation_item_inlines(item, options),
+        };
+        if part.is_empty() {
+            continue;
+        }
+        if !items.is_empty() {
+            let delimiter = item.group_delimiter.as_ref().unwrap_or(&citation.delimiter);
+            append_text(&mut items, delimiter);
+        }
+        items.extend(part);
+    }
+    let mut inlines = vec![
+        Inline::Text(open.to_string()),
+        Inline::Text(citation.prefix.clone().unwrap_or_default()),
+    ];
+    inlines.extend(items);
+    append_text(&mut inlines, &citation.suffix.clone().unwrap_or_default());
+    append_text(&mut inlines, close);
+    inlines.retain(|inline| inline != &Inline::Text(String::new()));
+    inlines
+}
+
+/// Replace each text in rich text with the rich text the function returns for it.
+fn map_text(inlines: Vec<Inline>, f: &impl Fn(String) -> Vec<Inline>) -> Vec<Inline> {
+    let mut result = Vec::with_capacity(inlines.len());
+    for inline in inlines {
+        match inline {
+            Inline::Text(text) => result.extend(f(text)),
+            Inline::Emph(content) => result.push(Inline::Emph(map_text(content, f))),
+            Inline::Strong(content) => result.push(Inline::Strong(map_text(content, f))),
+            Inline::Quoted(content) => result.push(Inline::Quoted(map_text(content, f))),
+            Inline::NoCase(content) => result.push(Inline::NoCase(map_text(content, f))),
+            Inline::Link { url, content } => {
+                result.push(Inline::Link { url, content: map_text(content, f) })
+            }
+        }
+    }
+    result
+}
+
+/// Join rich text with a delimiter, skipping empty parts and collapsing
+/// punctuation the delimiter would duplicate.
+pub fn join(parts: Vec<Vec<Inline>>, delimiter: &str) -> Vec<Inline> {
+    let mut inlines = Vec::new();
+    for part in parts.into_iter().filter(|part| !part.is_empty()) {
+        if !inlines.is_empty() {
+            append_text(&mut inlines, delimiter);
+        }
+        inlines.extend(part);
+    }
+    inlines
+}
+
+/// Append text, dropping its leading punctuation when the preceding content
+/// already ends with equivalent punctuation; so "Doe, J." and ". " give
+/// "Doe, J. ", and "Why?" and "." give "Why?".
+pub fn append_text(inlines: &mut Vec<Inline>, text: &str) {
+    let text = match (trailing_char(inlines), text.chars().next()) {
+        (Some(last), Some(first)) if collapses(last, first) => &text[first.len_utf8()..],
+        _ => text,
+    };
+    if !text.is_empty() {
+        push_inline(inlines, Inline::Text(text.to_string()));
+    }
+}
+
+/// Return the last character of the content, unless it is a closing quote.
+fn trailing_char(inlines: &[Inline]) -> Option<char> {
+    match inlines.last()? {
+        Inline::Text(text) => text.chars().next_back(),
+        Inline::Emph(content)
+        | Inline::Strong(content)
+        | Inline::NoCase(content)
+        | Inline::Link { content, .. } => trailing_char(content),
+        Inline::Quoted(_) => None,
+    }
+}
+
+/// Return the text of the content, without its formatting.
+pub(super) fn plain_text(inlines: &[Inline]) -> String {
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(text) => text.clone(),
+            Inline::Emph(content)
+            | Inline::Strong(content)
+            | Inline::Quoted(content)
+            | Inline::NoCase(content)
+            | Inline::Link { content, .. } => plain_text(content),
+        })
+        .collect()
+}
+
+/// Whether punctuation is redundant after the preceding character.
+fn collapses(last: char, next: char) -> bool {
+    matches!((last, next), ('.' | '?' | '!', '.') | (',', ',') | (';', ';') | (':', ':'))
+}
+
+/// Apply the locale's punctuation conventions to the rich text.
+pub fn localize_punctuation(inlines: Vec<Inline>, locale: &Locale) -> Vec<Inline> {
+    if locale.options.punctuation_in_quotes {
+        punctuation_in_quotes(inlines)
+    } else {
+        inlines
+    }
+}
+
+/// Split quotations within a string of reference data into nested quoted content.
+///
+/// Straight double quotes, and the locale's own outer quotation marks, are
+/// recognized when balanced; anything else is left as is.
+fn parse_quotes(text: &str, open: &str, close: &str) -> Vec<Inline> {
+    // Each level records what opened it, and its content so far.
+    let mut stack: Vec<(&str, Vec<Inline>)> = vec![("", Vec::new())];
+    let mut buffer = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let opener = stack.last().map(|(opener, _)| *opener).unwrap_or_default();
+        let token = if rest.starts_with('"') {
+            Some("\"")
+        } else if !open.is_empty() && rest.starts_with(open) {
+            Some(open)
+        } else if !close.is_empty() && rest.starts_with(close) {
+            Some(close)
+        } else {
+            None
+        };
+        match token {
+            Some(token)
+                if (token == "\"" && opener == "\"")
+                    || (token == close && token != open && opener == open) =>
+            {
+                push_text(&mut stack, &mut buffer);
+                if let Some((_, content)) = stack.pop() {
+                    if let Some((_, parent)) = stack.last_mut() {
+                        parent.push(Inline::Quoted(content));
+                    }
+                }
+                rest = &rest[token.len()..];
+            }
+            Some(token) if token == "\"" || token == open => {
+                push_text(&mut stack, &mut buffer);
+                stack.push((token, Vec::new()));
+                rest = &rest[token.len()..];
+            }
+            _ => {
+                buffer.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    push_text(&mut stack, &mut buffer);
+    // Unbalanced quotes are literal text.
+    while stack.len() > 1 {
+        if let Some((opener, content)) = stack.pop() {
+            if let Some((_, parent)) = stack.last_mut() {
+                push_inline(parent, Inline::Text(opener.to_string()));
+                content.into_iter().for_each(|inline| push_inline(parent, inline));
+            }
+        }
+    }
+    stack.pop().map(|(_, content)| content).unwrap_or_default()
+}
+
+/// Push the buffered text onto the innermost open content.
+pub(super) fn push_text<T>(stack: &mut [(T, Vec<Inline>)], buffer: &mut String) {
+    if let Some((_, content)) = stack.last_mut() {
+        if !buffer.is_empty() {
+            push_inline(content, Inline::Text(std::mem::take(buffer)));
+        }
+    }
+}
+
+/// Push an inline, merging adjacent text.
+pub(super) fn push_inline(content: &mut Vec<Inline>, inline: Inline) {
+    match (content.last_mut(), inline) {
+        (Some(Inline::Text(last)), Inline::Text(text)) => last.push_str(&text),
+        (_, inline) => content.push(inline),
+    }
+}
+
+/// Move periods and commas that directly follow closing quotes inside them.
+fn punctuation_in_quotes(inlines: Vec<Inline>) -> Vec<Inline> {
+    let mut result: Vec<Inline> = Vec::with_capacity(inlines.len());
+    for inline in inlines {
+        let inline = match inline {
+            Inline::Emph(content) => Inline::Emph(punctuation_in_quotes(content)),
+            Inline::Strong(content) => Inline::Strong(punctuation_in_quotes(content)),
+            Inline::Quoted(content) => Inline::Quoted(punctuation_in_quotes(content)),
+            Inline::NoCase(content) => Inline::NoCase(punctuation_in_quotes(content)),
+            Inline::Link { url, content } => {
+                Inline::Link { url, content: punctuation_in_quotes(content) }
+            }
+            Inline::Text(text) => match (result.last_mut(), text.chars().next()) {
+                (Some(Inline::Quoted(quoted)), Some(c @ ('.' | ','))) => {
+                    append_punctuation(quoted, c);
+                    Inline::Text(text[c.len_utf8()..].to_string())
+                }
+                _ => Inline::Text(text),
+            },
+        };
+        if inline != Inline::Text(String::new()) {
+            result.push(inline);
+        }
+    }
+    result
+}
+
+/// Append punctuation inside the innermost closing quote.
+fn append_punctuation(content: &mut Vec<Inline>, punctuation: char) {
+    match content.last_mut() {
+        Some(Inline::Quoted(quoted)) => append_punctuation(quoted, punctuation),
+        _ => append_text(content, &punctuation.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Inline {
+        Inline::Text(s.to_string())
+    }
+
+    #[test]
+    fn parses_nested_quotes() {
+        assert_eq!(
+            parse_quotes("The \"Real\" Thing", "“", "”"),
+            vec![text("The "), Inline::Quoted(vec![text("Real")]), text(" Thing")]
+        );
+        assert_eq!(
+            parse_quotes("On “Quotes”", "“", "”"),
+            vec![text("On "), Inline::Quoted(vec![text("Quotes")])]
+        );
+        assert_eq!(parse_quotes("5\" long", "“", "”"), vec![text("5\" long")]);
+    }
+
+    #[test]
+    fn collapses_duplicate_punctuation() {
+        let parts = vec![
+            vec![text("Doe, J.")],
+            vec![],
+            vec![Inline::Emph(vec![text("Why?")])],
+            vec![Inline::Quoted(vec![text("Title.")])],
+            vec![text("2020")],
+        ];
+        let mut inlines = join(parts, ". ");
+        append_text(&mut inlines, ".");
+        assert_eq!(
+            inlines,
+            vec![
+                text("Doe, J. "),
+                Inline::Emph(vec![text("Why?")]),
+                text(" "),
+                Inline::Quoted(vec![text("Title.")]),
+                text(". "),
+                text("2020."),
+            ]
+        );
+        assert_eq!(
+            punctuation_in_quotes(inlines[3..].to_vec()),
+            vec![Inline::Quoted(vec![text("Title.")]), text(" "), text("2020.")]
+        );
+    }
+
+    #[test]
+    fn collapses_affix_punctuation() {
+        use crate::types::{ProcTemplateComponent, ProcValues};
+        use csln::citation::Position;
+        use csln::style::template::{
+            ContributorForm, ContributorRole, Rendering, TemplateContributor,
+        };
+        let component = ProcTemplateComponent::new(
+            TemplateComponent::Contributor(TemplateContributor {
+                contributor: ContributorRole::Author,
+                form: ContributorForm::Long,
+                rendering: Some(Rendering {
+                    suffix: Some(".".to_string()),
+                    ..Default::default()
+                }),
+            }),
+            ProcValues {
+                value: "Doe, J.".to_string(),
+                prefix: None,
+                suffix: None,
+                url: None,
+            },
+        );
+        let options = RenderOptions::default();
+        assert_eq!(component_inlines(&component, &options), vec![text("Doe, J.")]);
+        let citation = ProcCitation {
+            prefix: None,
+            items: vec![ProcCitationItem {
+                prefix: None,
+                template: vec![component],
+                suffix: None,
+                delimiter: " ".to_string(),
+                group_delimiter: None,
+                position: Position::First,
+                near_note: false,
+            }],
+            suffix: Some(".".to_string()),
+            mode: CitationModeType::NonIntegral,
+            delimiter: "; ".to_string(),
+            wrap: WrapPunctuation::None,
+        };
+        assert_eq!(citation_inlines(&citation, &options), vec![text("Doe, J.")]);
+    }
+
+    #[test]
+    fn moves_punctuation_into_quotes() {
+        let inlines = vec![
+            Inline::Quoted(vec![text("A "), Inline::Quoted(vec![text("B")])]),
+            text(". "),
+            Inline::Quoted(vec![Inline::Emph(vec![text("C")])]),
+            text(","),
+        ];
+        assert_eq!(
+            punctuation_in_quotes(inlines),
+            vec![
+                Inline::Quoted(vec![text("A "), Inline::Quoted(vec![text("B.")])]),
+                text(" "),
+                Inline::Quoted(vec![Inline::Emph(vec![text("C")]), text(",")]),
+            ]
+        );
+    }
+}
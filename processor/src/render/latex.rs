@@ -0,0 +1,108 @@
+/*
+SPDX-License-Identifier: MPL-2.0
+SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
+*/
+
+use super::Renderer;
+
+/// Renders LaTeX markup.
+///
+/// Quotes are rendered with the `\enquote` command, and so require the `csquotes`
+/// package, which also takes care of the locale-specific quotation marks. Links
+/// use the `\href` command of the `hyperref` package.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Latex;
+
+impl Renderer for Latex {
+    fn text(&self, text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                    escaped.push('\\');
+                    escaped.push(c);
+                }
+                '~' => escaped.push_str("\\textasciitilde{}"),
+                '^' => escaped.push_str("\\textasciicircum{}"),
+                '\\' => escaped.push_str("\\textbackslash{}"),
+                _ => escaped.push(c),
+            }
+        }
+        escaped
+    }
+
+    fn emph(&self, content: String) -> String {
+        format!("\\emph{{{}}}", content)
+    }
+
+    fn strong(&self, content: String) -> String {
+        format!("\\textbf{{{}}}", content)
+    }
+
+    fn roman(&self, content: String) -> String {
+        format!("\\textup{{{}}}", content)
+    }
+
+    fn quote(&self, content: String, _open: &str, _close: &str) -> String {
+        format!("\\enquote{{{}}}", content)
+    }
+
+    fn link(&self, content: String, url: &str) -> String {
+        let mut escaped = String::with_capacity(url.len());
+        for c in url.chars() {
+            if matches!(c, '%' | '#' | '{' | '}') {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        format!("\\href{{{}}}{{{}}}", escaped, content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::title;
+    use crate::types::{ProcTemplateComponent, ProcValues, RenderOptions};
+    use csln::style::template::{
+        Rendering, TemplateComponent, TemplateSimpleString, Variables,
+    };
+
+    #[test]
+    fn renders_formatting() {
+        let component = title(
+            "Title",
+            Rendering {
+                emph: Some(true),
+                quote: Some(true),
+                strong: Some(true),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            Latex.render_component(&component, &RenderOptions::default()),
+            "\\enquote{\\textbf{\\emph{Title}}}"
+        );
+    }
+
+    #[test]
+    fn escapes_special_characters() {
+        assert_eq!(Latex.text("R&D 100% #1 {x}"), "R\\&D 100\\% \\#1 \\{x\\}");
+        let component = ProcTemplateComponent::new(
+            TemplateComponent::SimpleString(TemplateSimpleString {
+                variable: Variables::Doi,
+                rendering: None,
+            }),
+            ProcValues {
+                value: "10.1002/(SICI)1097_4571~x".to_string(),
+                prefix: None,
+                suffix: None,
+                url: None,
+            },
+        );
+        assert_eq!(
+            Latex.render_component(&component, &RenderOptions::default()),
+            "10.1002/(SICI)1097\\_4571\\textasciitilde{}x"
+        );
+    }
+}
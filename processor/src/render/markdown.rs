@@ -0,0 +1,121 @@
+/*
+SPDX-License-Identifier: MPL-2.0
+SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
+*/
+
+use super::{encode_url, mark_roman, wrap_runs, Renderer};
+
+/// Renders CommonMark, which is also valid Pandoc Markdown.
+///
+/// Characters Pandoc gives special meaning to, such as `@` for citations and `~`
+/// for subscripts, are escaped as well.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Markdown;
+
+impl Renderer for Markdown {
+    fn text(&self, text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            if matches!(
+                c,
+                '\\' | '*'
+                    | '_'
+                    | '`'
+                    | '['
+                    | ']'
+                    | '<'
+                    | '>'
+                    | '#'
+                    | '~'
+                    | '^'
+                    | '$'
+                    | '@'
+            ) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    }
+
+    fn emph(&self, content: String) -> String {
+        wrap_runs(&content, "*", "*", true)
+    }
+
+    fn strong(&self, content: String) -> String {
+        wrap_runs(&content, "**", "**", false)
+    }
+
+    fn roman(&self, content: String) -> String {
+        mark_roman(content)
+    }
+
+    fn link(&self, content: String, url: &str) -> String {
+        format!("[{}]({})", content, encode_url(url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::title;
+    use crate::types::RenderOptions;
+    use csln::style::template::Rendering;
+
+    #[test]
+    fn renders_formatting() {
+        let component = title(
+            "On @mentions_",
+            Rendering {
+                emph: Some(true),
+                quote: Some(true),
+                strong: Some(true),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            Markdown.render_component(&component, &RenderOptions::default()),
+            "“***On \\@mentions\\_***”"
+        );
+    }
+
+    #[test]
+    fn renders_links() {
+        let mut component = title("10.1002/(SICI)1097", Rendering::default());
+        component.values.url = Some("https://doi.org/10.1002/(SICI)1097".to_string());
+        assert_eq!(
+            Markdown.render_component(&component, &RenderOptions::default()),
+            "[10.1002/(SICI)1097](https://doi.org/10.1002/%28SICI%291097)"
+        );
+    }
+
+    #[test]
+    fn keeps_whitespace_outside_delimiters() {
+        assert_eq!(Markdown.emph(" Title ".to_string()), " *Title* ");
+        assert_eq!(Markdown.strong("Title\n".to_string()), "**Title**\n");
+        assert_eq!(Markdown.emph(" ".to_string()), " ");
+    }
+
+    #[test]
+    fn renders_roman_within_emphasis() {
+        use crate::render::Inline;
+        let text = |text: &str| Inline::Text(text.to_string());
+        let inlines = [Inline::Emph(vec![
+            text("On "),
+            Inline::Emph(vec![text("Homo sapiens")]),
+            text(" in Titles"),
+        ])];
+        assert_eq!(
+            Markdown.render_inlines(&inlines, &RenderOptions::default()),
+            "*On* Homo sapiens *in Titles*"
+        );
+        let inlines = [Inline::Emph(vec![Inline::Strong(vec![
+            text("On "),
+            Inline::Emph(vec![text("Homo")]),
+        ])])];
+        assert_eq!(
+            Markdown.render_inlines(&inlines, &RenderOptions::default()),
+            "***On*** **Homo**"
+        );
+    }
+}
@@ -0,0 +1,317 @@
+/*
+SPDX-License-Identifier: MPL-2.0
+SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
+*/
+
+//! Markup within reference data, and text case transforms that respect it.
+//!
+//! Titles may use a small subset of HTML: `<i>` (or `<em>`) for emphasis, `<b>`
+//! (or `<strong>`) for strong emphasis, and `<span class="nocase">` to protect
+//! text, such as proper nouns, from case transforms.
+
+use super::inline::{plain_text, push_inline, push_text};
+use super::Inline;
+use csln::style::template::TextCase;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Tag {
+    Emph,
+    Strong,
+    NoCase,
+}
+
+const TAGS: [(&str, &str, Tag); 5] = [
+    ("<i>", "</i>", Tag::Emph),
+    ("<em>", "</em>", Tag::Emph),
+    ("<b>", "</b>", Tag::Strong),
+    ("<strong>", "</strong>", Tag::Strong),
+    ("<span class=\"nocase\">", "</span>", Tag::NoCase),
+];
+
+/// Words that title case leaves lowercase, other than as the first word.
+const STOP_WORDS: [&str; 18] = [
+    "a", "an", "and", "as", "at", "but", "by", "for", "from", "in", "into", "nor", "of",
+    "on", "or", "the", "to", "with",
+];
+
+/// Parse the markup in a string of reference data.
+///
+/// Unknown and unbalanced tags are left as literal text.
+pub fn parse_markup(text: &str) -> Vec<Inline> {
+    // Each level records the tag that opened it, and its content so far.
+    let mut stack: Vec<(Option<(&str, &str, Tag)>, Vec<Inline>)> =
+        vec![(None, Vec::new())];
+    let mut buffer = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let current = stack.last().and_then(|(tag, _)| *tag);
+        if let Some((_, close, tag)) =
+            current.filter(|(_, close, _)| rest.starts_with(close))
+        {
+            push_text(&mut stack, &mut buffer);
+            if let Some((_, content)) = stack.pop() {
+                if let Some((_, parent)) = stack.last_mut() {
+                    parent.push(match tag {
+                        Tag::Emph => Inline::Emph(content),
+                        Tag::Strong => Inline::Strong(content),
+                        Tag::NoCase => Inline::NoCase(content),
+                    });
+                }
+            }
+            rest = &rest[close.len()..];
+        } else if let Some(opened) =
+            TAGS.iter().find(|(open, _, _)| rest.starts_with(open))
+        {
+            push_text(&mut stack, &mut buffer);
+            stack.push((Some(*opened), Vec::new()));
+            rest = &rest[opened.0.len()..];
+        } else {
+            buffer.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    push_text(&mut stack, &mut buffer);
+    // Unclosed tags are literal text.
+    while stack.len() > 1 {
+        if let Some((tag, content)) = stack.pop() {
+            if let Some((_, parent)) = stack.last_mut() {
+                let open = tag.map(|(open, _, _)| open).unwrap_or_default();
+                push_inline(parent, Inline::Text(open.to_string()));
+                content.into_iter().for_each(|inline| push_inline(parent, inline));
+            }
+        }
+    }
+    stack.pop().map(|(_, content)| content).unwrap_or_default()
+}
+
+/// Return the text of a string of reference data, without its markup.
+pub fn strip_markup(text: &str) -> String {
+    plain_text(&parse_markup(text))
+}
+
+/// Apply a case transform to rich text, other than to its nocase content.
+pub fn change_case(inlines: &mut [Inline], case: &TextCase) {
+    let (upper, lower) = letter_cases(inlines);
+    let uppercase = upper && !lower;
+    change_case_from(inlines, case, &mut CaseState { uppercase, ..Default::default() });
+}
+
+/// Whether rich text, other than its nocase content, has uppercase and lowercase
+/// letters.
+fn letter_cases(inlines: &[Inline]) -> (bool, bool) {
+    inlines.iter().fold((false, false), |(upper, lower), inline| {
+        let (has_upper, has_lower) = match inline {
+            Inline::Text(text) => (
+                text.chars().any(char::is_uppercase),
+                text.chars().any(char::is_lowercase),
+            ),
+            Inline::NoCase(_) => (false, false),
+            Inline::Emph(content)
+            | Inline::Strong(content)
+            | Inline::Quoted(content)
+            | Inline::Link { content, .. } => letter_cases(content),
+        };
+        (upper || has_upper, lower || has_lower)
+    })
+}
+
+/// Where a case transform is in the text.
+#[derive(Default)]
+struct CaseState {
+    /// Whether a word has started.
+    started: bool,
+    /// Whether the last character was part of a word.
+    in_word: bool,
+    /// Whether the last word ended with a colon.
+    after_colon: bool,
+    /// Whether the text, other than its nocase content, is all uppercase.
+    uppercase: bool,
+}
+
+fn change_case_from(inlines: &mut [Inline], case: &TextCase, state: &mut CaseState) {
+    for inline in inlines {
+        match inline {
+            Inline::Text(text) => *text = change_text_case(text, case, state),
+            Inline::NoCase(content) => skip_case(content, state),
+            Inline::Emph(content)
+            | Inline::Strong(content)
+            | Inline::Quoted(content)
+            | Inline::Link { content, .. } => change_case_from(content, case, state),
+        }
+    }
+}
+
+/// Track the position of protected text, which later words depend on.
+fn skip_case(inlines: &[Inline], state: &mut CaseState) {
+    for inline in inlines {
+        match inline {
+            Inline::Text(text) => {
+                for c in text.chars() {
+                    state.in_word = !c.is_whitespace();
+                    state.started |= state.in_word;
+                    if state.in_word {
+                        state.after_colon = c == ':';
+                    }
+                }
+            }
+            Inline::Emph(content)
+            | Inline::Strong(content)
+            | Inline::Quoted(content)
+            | Inline::NoCase(content)
+            | Inline::Link { content, .. } => skip_case(content, state),
+        }
+    }
+}
+
+fn change_text_case(text: &str, case: &TextCase, state: &mut CaseState) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            state.in_word = false;
+            result.push(c);
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let word = &rest[..end];
+        let first = !state.started;
+        let continued = state.in_word;
+        let after_colon = state.after_colon;
+        state.started = true;
+        state.in_word = true;
+        state.after_colon = word.ends_with(':');
+        rest = &rest[end..];
+        let capitalize = !continued
+            && match case {
+                TextCase::Lowercase => {
+                    result.push_str(&word.to_lowercase());
+                    continue;
+                }
+                TextCase::Uppercase => {
+                    result.push_str(&word.to_uppercase());
+                    continue;
+                }
+                // Only text in all uppercase is lowercased, as other capitals may
+                // be proper nouns or acronyms.
+                TextCase::Sentence if state.uppercase => {
+                    let word = word.to_lowercase();
+                    result.push_str(&if first { capitalize_first(&word) } else { word });
+                    continue;
+                }
+                TextCase::CapitalizeFirst | TextCase::Sentence => first,
+                TextCase::Title => {
+                    let bare = word.trim_matches(|c: char| !c.is_alphanumeric());
+                    first
+                        || after_colon
+                        || !STOP_WORDS.contains(&bare.to_lowercase().as_str())
+                }
+            };
+        if capitalize && !word.chars().any(char::is_uppercase) {
+            result.push_str(&capitalize_first(word));
+        } else {
+            result.push_str(word);
+        }
+    }
+    result
+}
+
+/// Uppercase the first letter of a word.
+fn capitalize_first(word: &str) -> String {
+    match word.char_indices().find(|(_, c)| c.is_alphabetic()) {
+        Some((index, c)) => {
+            word[..index].to_string()
+                + &c.to_uppercase().to_string()
+                + &word[index + c.len_utf8()..]
+        }
+        None => word.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Inline {
+        Inline::Text(s.to_string())
+    }
+
+    #[test]
+    fn parses_markup() {
+        assert_eq!(
+            parse_markup(
+                "On <i>Homo <b>sapiens</b></i> in <span class=\"nocase\">iOS</span>"
+            ),
+            vec![
+                text("On "),
+                Inline::Emph(vec![text("Homo "), Inline::Strong(vec![text("sapiens")])]),
+                text(" in "),
+                Inline::NoCase(vec![text("iOS")]),
+            ]
+        );
+        assert_eq!(
+            parse_markup("a <i>b</b> <u>c</u>"),
+            vec![text("a <i>b</b> <u>c</u>")]
+        );
+    }
+
+    #[test]
+    fn changes_case_except_nocase() {
+        let mut inlines =
+            parse_markup("the rise of <span class=\"nocase\">eBay</span> in the US");
+        change_case(&mut inlines, &TextCase::Title);
+        assert_eq!(
+            inlines,
+            vec![
+                text("The Rise of "),
+                Inline::NoCase(vec![text("eBay")]),
+                text(" in the US"),
+            ]
+        );
+        let mut inlines = parse_markup("<span class=\"nocase\">iPhone</span> sales");
+        change_case(&mut inlines, &TextCase::Sentence);
+        assert_eq!(inlines, vec![Inline::NoCase(vec![text("iPhone")]), text(" sales")]);
+        let mut inlines = parse_markup(
+            "The Rise of <span class=\"nocase\">eBay</span> in the US: A History",
+        );
+        change_case(&mut inlines, &TextCase::Sentence);
+        assert_eq!(
+            inlines,
+            vec![
+                text("The Rise of "),
+                Inline::NoCase(vec![text("eBay")]),
+                text(" in the US: A History"),
+            ]
+        );
+        let mut inlines = parse_markup("WAR AND PEACE");
+        change_case(&mut inlines, &TextCase::Sentence);
+        assert_eq!(inlines, vec![text("War and peace")]);
+        let mut inlines = parse_markup("the <i>NASA</i> years");
+        change_case(&mut inlines, &TextCase::Sentence);
+        assert_eq!(
+            inlines,
+            vec![text("The "), Inline::Emph(vec![text("NASA")]), text(" years")]
+        );
+        let mut inlines = parse_markup("the <i>study</i>: a history of the world");
+        change_case(&mut inlines, &TextCase::Title);
+        assert_eq!(
+            inlines,
+            vec![
+                text("The "),
+                Inline::Emph(vec![text("Study")]),
+                text(": A History of the World"),
+            ]
+        );
+        let mut inlines = parse_markup("<i>multi</i>part Words");
+        change_case(&mut inlines, &TextCase::Lowercase);
+        assert_eq!(inlines, vec![Inline::Emph(vec![text("multi")]), text("part words")]);
+    }
+
+    #[test]
+    fn strips_markup() {
+        assert_eq!(
+            strip_markup("<i>The Book</i> of <span class=\"nocase\">iOS</span> <u>"),
+            "The Book of iOS <u>"
+        );
+    }
+}
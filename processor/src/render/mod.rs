@@ -0,0 +1,451 @@
+/*
+SPDX-License-Identifier: MPL-2.0
+SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
+*/
+
+//! Rendering of the processed intermediate representation to output formats.
+//!
+//! A [`Renderer`] implements a small set of formatting primitives for its target
+//! format; the provided methods use those to assemble components, references,
+//! citations and bibliographies. [`PlainText`] is the default renderer.
+
+use crate::types::{
+    ProcBibliography, ProcCitation, ProcCitationItem, ProcReferences, ProcTemplate,
+    ProcTemplateComponent, RenderOptions,
+};
+use csln::style::template::WrapPunctuation;
+use std::fmt::{self, Display, Formatter};
+
+mod inline;
+mod markup;
+pub use inline::Inline;
+use inline::{
+    append_text, citation_inlines, citation_item_inlines, component_inlines, join,
+    localize_punctuation, quote_marks,
+};
+pub(crate) use markup::strip_markup;
+
+mod djot;
+pub use djot::Djot;
+mod html;
+pub use html::Html;
+mod latex;
+pub use latex::Latex;
+mod markdown;
+pub use markdown::Markdown;
+mod odf;
+pub use odf::Odf;
+mod ooxml;
+pub use ooxml::Ooxml;
+mod plain;
+pub use plain::PlainText;
+mod rtf;
+pub use rtf::Rtf;
+mod typst;
+pub use typst::Typst;
+
+/// Render processed references to an output format.
+pub trait Renderer {
+    /// Escape a string of reference data or style punctuation for the output format.
+    fn text(&self, text: &str) -> String;
+
+    /// Render emphasized (typically italic) content.
+    fn emph(&self, content: String) -> String {
+        content
+    }
+
+    /// Render strong (typically bold) content.
+    fn strong(&self, content: String) -> String {
+        content
+    }
+
+    /// Render content emphasized within emphasized content, which is usually
+    /// upright; formats that cannot express it render the content.
+    fn roman(&self, content: String) -> String {
+        content
+    }
+
+    /// Render quoted content, using the locale's quotation marks.
+    fn quote(&self, content: String, open: &str, close: &str) -> String {
+        self.text(open) + &content + &self.text(close)
+    }
+
+    /// Render content quoted within quoted content, using the locale's inner
+    /// quotation marks.
+    fn inner_quote(&self, content: String, open: &str, close: &str) -> String {
+        self.quote(content, open, close)
+    }
+
+    /// Render content linked to a URL; formats without links render the content.
+    fn link(&self, content: String, _url: &str) -> String {
+        content
+    }
+
+    /// Wrap a single rendered bibliography entry.
+    fn entry(&self, content: String) -> String {
+        content
+    }
+
+    /// Join the rendered bibliography entries into a bibliography.
+    fn bibliography(&self, entries: Vec<String>) -> String {
+        entries.join("\n\n")
+    }
+
+    /// Render rich text.
+    fn render_inlines(&self, inlines: &[Inline], options: &RenderOptions) -> String {
+        render_inlines_at(self, inlines, 0, false, options)
+    }
+
+    /// Render a single template component, including its affixes and wrapping.
+    fn render_component(
+        &self,
+        component: &ProcTemplateComponent,
+        options: &RenderOptions,
+    ) -> String {
+        let inlines = component_inlines(component, options);
+        self.render_inlines(&localize_punctuation(inlines, &options.locale), options)
+    }
+
+    /// Render the components of a reference.
+    fn render_template(
+        &self,
+        template: &ProcTemplate,
+        options: &RenderOptions,
+    ) -> String {
+        // A leading citation number is a label, as in "[1] Doe, J. …".
+        let (label, template) = match template.split_first() {
+            Some((first, rest)) if first.template_component.is_citation_number() => {
+                (Some(first), rest)
+            }
+            _ => (None, &template[..]),
+        };
+        let parts = template
+            .iter()
+            .map(|component| component_inlines(component, options))
+            .collect();
+        let mut inlines = join(parts, ". ");
+        append_text(&mut inlines, ".");
+        if let Some(label) = label {
+            inlines = join(vec![component_inlines(label, options), inlines], " ");
+        }
+        self.render_inlines(&localize_punctuation(inlines, &options.locale), options)
+    }
+
+    /// Render a bibliography.
+    fn render_bibliography(
+        &self,
+        bibliography: &ProcBibliography,
+        options: &RenderOptions,
+    ) -> String {
+        self.bibliography(
+            bibliography
+                .iter()
+                .map(|proc_template| {
+                    self.entry(self.render_template(proc_template, options))
+                })
+                .collect(),
+        )
+    }
+
+    /// Render a single citation item.
+    fn render_citation_item(
+        &self,
+        item: &ProcCitationItem,
+        options: &RenderOptions,
+    ) -> String {
+        let inlines = citation_item_inlines(item, options);
+        self.render_inlines(&localize_punctuation(inlines, &options.locale), options)
+    }
+
+    /// Render a citation.
+    fn render_citation(
+        &self,
+        citation: &ProcCitation,
+        options: &RenderOptions,
+    ) -> String {
+        let inlines = citation_inlines(citation, options);
+        self.render_inlines(&localize_punctuation(inlines, &options.locale), options)
+    }
+
+    /// Render the citations, one per line, followed by the bibliography.
+    fn render_references(
+        &self,
+        references: &ProcReferences,
+        options: &RenderOptions,
+    ) -> String {
+        let bibliography = self.render_bibliography(&references.bibliography, options);
+        match &references.citations {
+            Some(citations) => {
+                citations
+                    .iter()
+                    .map(|citation| self.render_citation(citation, options))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+                    + "\n\n"
+                    + &bibliography
+            }
+            None => bibliography,
+        }
+    }
+}
+
+/// Render rich text, alternating the quotation marks of nested quotes, and
+/// emphasis within emphasis.
+fn render_inlines_at<R: Renderer + ?Sized>(
+    renderer: &R,
+    inlines: &[Inline],
+    quote_depth: usize,
+    emphasized: bool,
+    options: &RenderOptions,
+) -> String {
+    let render = |content: &[Inline], quote_depth: usize, emphasized: bool| {
+        render_inlines_at(renderer, content, quote_depth, emphasized, options)
+    };
+    inlines
+        .iter()
+        .map(|inline| match inline {
+            Inline::Text(text) => renderer.text(text),
+            Inline::Emph(content) if emphasized => {
+                renderer.roman(render(content, quote_depth, false))
+            }
+            Inline::Emph(content) => renderer.emph(render(content, quote_depth, true)),
+            Inline::Strong(content) => {
+                renderer.strong(render(content, quote_depth, emphasized))
+            }
+            Inline::NoCase(content) => render(content, quote_depth, emphasized),
+            Inline::Quoted(content) => {
+                let content = render(content, quote_depth + 1, emphasized);
+                let inner = quote_depth % 2 == 1;
+                let (open, close) = quote_marks(&options.locale, inner);
+                if inner {
+                    renderer.inner_quote(content, open, close)
+                } else {
+                    renderer.quote(content, open, close)
+                }
+            }
+            Inline::Link { url, content } => {
+                renderer.link(render(content, quote_depth, emphasized), url)
+            }
+        })
+        .collect()
+}
+
+/// Return the opening and closing characters for the wrap punctuation.
+pub fn wrap_punctuation(wrap: &WrapPunctuation) -> (&'static str, &'static str) {
+    match wrap {
+        WrapPunctuation::None => ("", ""),
+        WrapPunctuation::Parentheses => ("(", ")"),
+        WrapPunctuation::Brackets => ("[", "]"),
+    }
+}
+
+/// Escape the characters with special meaning in HTML and XML.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Percent-encode the characters that would end a link destination early.
+fn encode_url(url: &str) -> String {
+    url.replace(' ', "%20").replace('(', "%28").replace(')', "%29")
+}
+
+/// Wrap content in delimiters that cannot open after, or close before, whitespace,
+/// as in Markdown; so leading and trailing whitespace goes outside them.
+fn wrap_inline(content: &str, open: &str, close: &str) -> String {
+    let trimmed = content.trim();
+    if trimmed.is_empty() {
+        return content.to_string();
+    }
+    let start = content.len() - content.trim_start().len();
+    let end = start + trimmed.len();
+    format!("{}{}{}{}{}", &content[..start], open, trimmed, close, &content[end..])
+}
+
+/// Private-use characters that mark content rendered roman within emphasis, for
+/// formats that close and reopen their delimiters around it.
+const ROMAN_START: char = '\u{E000}';
+const ROMAN_END: char = '\u{E001}';
+
+/// Mark content to render roman within emphasis; see [`wrap_runs`].
+fn mark_roman(content: String) -> String {
+    format!("{}{}{}", ROMAN_START, content, ROMAN_END)
+}
+
+/// Wrap content in delimiters, as with [`wrap_inline`], closing and reopening them
+/// around content marked roman. Emphasis leaves the marked content out; other
+/// formatting wraps it too, and keeps it marked for the emphasis around.
+fn wrap_runs(content: &str, open: &str, close: &str, emphasis: bool) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find(ROMAN_START) {
+        result.push_str(&wrap_inline(&rest[..start], open, close));
+        let marked = &rest[start + ROMAN_START.len_utf8()..];
+        let end = marked.find(ROMAN_END).unwrap_or(marked.len());
+        if emphasis {
+            result.push_str(&marked[..end]);
+        } else {
+            result.push(ROMAN_START);
+            result.push_str(&wrap_inline(&marked[..end], open, close));
+            result.push(ROMAN_END);
+        }
+        rest = marked.get(end + ROMAN_END.len_utf8()..).unwrap_or_default();
+    }
+    result.push_str(&wrap_inline(rest, open, close));
+    result
+}
+
+/// Render a bibliography as plain text.
+pub fn refs_to_string(proc_templates: Vec<ProcTemplate>) -> String {
+    PlainText.render_bibliography(&proc_templates, &RenderOptions::default())
+}
+
+impl Display for ProcTemplateComponent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", PlainText.render_component(self, &RenderOptions::default()))
+    }
+}
+
+/// Return a primary title component with the value and rendering, for renderer
+/// tests.
+#[cfg(test)]
+fn title(
+    value: &str,
+    rendering: csln::style::template::Rendering,
+) -> ProcTemplateComponent {
+    use crate::types::ProcValues;
+    use csln::style::template::{TemplateComponent, TemplateTitle, Titles};
+    ProcTemplateComponent::new(
+        TemplateComponent::Title(TemplateTitle {
+            title: Titles::Primary,
+            form: None,
+            rendering: Some(rendering),
+        }),
+        ProcValues {
+            value: value.to_string(),
+            prefix: None,
+            suffix: None,
+            url: None,
+        },
+    )
+}
+
+#[test]
+fn render_proc_template_component() {
+    use crate::types::ProcValues;
+    use csln::style::template::{
+        Rendering, TemplateComponent, TemplateSimpleString, Variables,
+    };
+    let template_component = TemplateComponent::SimpleString(TemplateSimpleString {
+        variable: Variables::Doi,
+        rendering: Some(Rendering {
+            emph: Some(true),
+            quote: Some(true),
+            strong: Some(true),
+            prefix: Some("doi: ".to_string()),
+            suffix: Some(" ||".to_string()),
+            wrap: Some(WrapPunctuation::Parentheses),
+            text_case: None,
+        }),
+    });
+    let value = "10/1234".to_string();
+    let proc_template_component = ProcTemplateComponent::new(
+        template_component,
+        ProcValues { value, prefix: None, suffix: None, url: None },
+    );
+    assert_eq!(proc_template_component.to_string(), "(doi: “10/1234” ||)".to_string());
+}
+
+#[test]
+fn render_bibliography_plain_text() {
+    use crate::types::ProcValues;
+    use csln::style::template::{DateForm, Dates, TemplateComponent, TemplateDate};
+    let year = |value: &str| {
+        ProcTemplateComponent::new(
+            TemplateComponent::Date(TemplateDate {
+                date: Dates::Issued,
+                form: DateForm::Year,
+                rendering: None,
+            }),
+            ProcValues {
+                value: value.to_string(),
+                prefix: None,
+                suffix: None,
+                url: None,
+            },
+        )
+    };
+    let bibliography = vec![vec![year("2020"), year("2021")], vec![year("2022")]];
+    assert_eq!(refs_to_string(bibliography), "2020. 2021.\n\n2022.");
+}
+
+#[test]
+fn render_citation_plain_text() {
+    use crate::types::ProcValues;
+    use csln::citation::{CitationModeType, Position};
+    use csln::style::template::{
+        ContributorForm, ContributorRole, DateForm, Dates, TemplateComponent,
+        TemplateContributor, TemplateDate,
+    };
+    let values = |value: &str| ProcValues {
+        value: value.to_string(),
+        prefix: None,
+        suffix: None,
+        url: None,
+    };
+    let item = |author: &str, year: &str, suffix: Option<&str>| ProcCitationItem {
+        prefix: None,
+        template: vec![
+            ProcTemplateComponent::new(
+                TemplateComponent::Contributor(TemplateContributor {
+                    contributor: ContributorRole::Author,
+                    form: ContributorForm::Short,
+                    rendering: None,
+                }),
+                values(author),
+            ),
+            ProcTemplateComponent::new(
+                TemplateComponent::Date(TemplateDate {
+                    date: Dates::Issued,
+                    form: DateForm::Year,
+                    rendering: None,
+                }),
+                values(year),
+            ),
+        ],
+        suffix: suffix.map(str::to_string),
+        delimiter: " ".to_string(),
+        group_delimiter: None,
+        position: Position::First,
+        near_note: false,
+    };
+    let mut citation = ProcCitation {
+        prefix: Some("see ".to_string()),
+        items: vec![item("Doe", "2020", Some("42")), item("Smith", "2021", None)],
+        suffix: None,
+        mode: CitationModeType::NonIntegral,
+        delimiter: "; ".to_string(),
+        wrap: WrapPunctuation::Parentheses,
+    };
+    assert_eq!(
+        PlainText.render_citation(&citation, &RenderOptions::default()),
+        "(see Doe 2020, 42; Smith 2021)"
+    );
+    citation.prefix = None;
+    citation.mode = CitationModeType::Integral;
+    citation.delimiter = ", ".to_string();
+    assert_eq!(
+        PlainText.render_citation(&citation, &RenderOptions::default()),
+        "Doe (2020, 42), Smith (2021)"
+    );
+}
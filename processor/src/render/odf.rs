@@ -0,0 +1,105 @@
+/*
+SPDX-License-Identifier: MPL-2.0
+SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
+*/
+
+use super::{escape_xml, Renderer};
+
+/// Renders OpenDocument text fragments.
+///
+/// Formatting uses the `Emphasis` and `Strong Emphasis` text styles, and
+/// bibliography entries the `Bibliography 1` paragraph style, which office suites
+/// define by default. Upright text within emphasis uses the `Roman` automatic
+/// style, which the document defines from [`Odf::AUTOMATIC_STYLES`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Odf;
+
+impl Odf {
+    /// The automatic styles the rendered fragments use, for the
+    /// `office:automatic-styles` of the document.
+    pub const AUTOMATIC_STYLES: &'static str =
+        "<style:style style:name=\"Roman\" style:family=\"text\">\
+         <style:text-properties fo:font-style=\"normal\"/></style:style>";
+}
+
+impl Renderer for Odf {
+    fn text(&self, text: &str) -> String {
+        escape_xml(text)
+    }
+
+    fn emph(&self, content: String) -> String {
+        format!("<text:span text:style-name=\"Emphasis\">{}</text:span>", content)
+    }
+
+    fn strong(&self, content: String) -> String {
+        format!(
+            "<text:span text:style-name=\"Strong_20_Emphasis\">{}</text:span>",
+            content
+        )
+    }
+
+    fn roman(&self, content: String) -> String {
+        format!("<text:span text:style-name=\"Roman\">{}</text:span>", content)
+    }
+
+    fn link(&self, content: String, url: &str) -> String {
+        format!(
+            "<text:a xlink:type=\"simple\" xlink:href=\"{}\">{}</text:a>",
+            escape_xml(url),
+            content
+        )
+    }
+
+    fn entry(&self, content: String) -> String {
+        format!("<text:p text:style-name=\"Bibliography_20_1\">{}</text:p>", content)
+    }
+
+    fn bibliography(&self, entries: Vec<String>) -> String {
+        entries.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::title;
+    use crate::types::RenderOptions;
+    use csln::style::template::Rendering;
+
+    #[test]
+    fn renders_formatted_spans() {
+        let component = title(
+            "Q&A",
+            Rendering {
+                emph: Some(true),
+                strong: Some(true),
+                ..Default::default()
+            },
+        );
+        let bibliography = vec![vec![component]];
+        assert_eq!(
+            Odf.render_bibliography(&bibliography, &RenderOptions::default()),
+            "<text:p text:style-name=\"Bibliography_20_1\">\
+             <text:span text:style-name=\"Strong_20_Emphasis\">\
+             <text:span text:style-name=\"Emphasis\">Q&amp;A</text:span>\
+             </text:span>.</text:p>"
+        );
+    }
+
+    #[test]
+    fn renders_roman_within_emphasis() {
+        use crate::render::Inline;
+        let text = |text: &str| Inline::Text(text.to_string());
+        let inlines = [Inline::Emph(vec![
+            text("On "),
+            Inline::Emph(vec![text("Homo sapiens")]),
+            text(" in Titles"),
+        ])];
+        assert_eq!(
+            Odf.render_inlines(&inlines, &RenderOptions::default()),
+            "<text:span text:style-name=\"Emphasis\">On \
+             <text:span text:style-name=\"Roman\">Homo sapiens</text:span> in Titles\
+             </text:span>"
+        );
+    }
+}
@@ -0,0 +1,125 @@
+/*
+SPDX-License-Identifier: MPL-2.0
+SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
+*/
+
+use super::{escape_xml, Renderer};
+
+/// Renders Office Open XML (WordprocessingML) fragments.
+///
+/// Citations render as sequences of `<w:r>` runs, and bibliography entries as
+/// `<w:p>` paragraphs with a hanging indent. Since runs cannot nest, formatting
+/// is added to the properties of every run it applies to.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Ooxml;
+
+impl Renderer for Ooxml {
+    fn text(&self, text: &str) -> String {
+        if text.is_empty() {
+            return String::new();
+        }
+        // Every run has a (possibly empty) property element for formatting to add to.
+        format!(
+            "<w:r><w:rPr></w:rPr><w:t xml:space=\"preserve\">{}</w:t></w:r>",
+            escape_xml(text)
+        )
+    }
+
+    fn emph(&self, content: String) -> String {
+        add_run_property(&content, "<w:i", "<w:i/>")
+    }
+
+    fn strong(&self, content: String) -> String {
+        add_run_property(&content, "<w:b", "<w:b/>")
+    }
+
+    fn roman(&self, content: String) -> String {
+        add_run_property(&content, "<w:i", "<w:i w:val=\"0\"/>")
+    }
+
+    fn link(&self, content: String, url: &str) -> String {
+        // A HYPERLINK field, since hyperlink elements need a relationship in the
+        // document part.
+        let url = escape_xml(&url.replace('"', "%22"));
+        format!(
+            "<w:r><w:fldChar w:fldCharType=\"begin\"/></w:r>\
+             <w:r><w:instrText xml:space=\"preserve\"> HYPERLINK \"{}\" </w:instrText></w:r>\
+             <w:r><w:fldChar w:fldCharType=\"separate\"/></w:r>\
+             {}<w:r><w:fldChar w:fldCharType=\"end\"/></w:r>",
+            url, content
+        )
+    }
+
+    fn entry(&self, content: String) -> String {
+        format!(
+            "<w:p><w:pPr><w:ind w:left=\"720\" w:hanging=\"720\"/></w:pPr>{}</w:p>",
+            content
+        )
+    }
+
+    fn bibliography(&self, entries: Vec<String>) -> String {
+        entries.join("\n")
+    }
+}
+
+/// Add a property to the runs that do not already set it, as the formatting of
+/// nested content takes precedence.
+fn add_run_property(content: &str, name: &str, property: &str) -> String {
+    let mut runs = content.split("<w:rPr>");
+    let mut result = runs.next().unwrap_or_default().to_string();
+    for run in runs {
+        result.push_str("<w:rPr>");
+        match run.find("</w:rPr>") {
+            Some(end) if !run[..end].contains(name) => {
+                // The schema requires bold to precede italic.
+                if name == "<w:b" {
+                    result.push_str(property);
+                    result.push_str(run);
+                } else {
+                    result.push_str(&run[..end]);
+                    result.push_str(property);
+                    result.push_str(&run[end..]);
+                }
+            }
+            _ => result.push_str(run),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::title;
+    use crate::types::RenderOptions;
+    use csln::style::template::Rendering;
+
+    #[test]
+    fn renders_formatted_runs() {
+        let component = title(
+            "Q&A",
+            Rendering {
+                emph: Some(true),
+                strong: Some(true),
+                prefix: Some("In ".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            Ooxml.render_component(&component, &RenderOptions::default()),
+            "<w:r><w:rPr></w:rPr><w:t xml:space=\"preserve\">In </w:t></w:r>\
+             <w:r><w:rPr><w:b/><w:i/></w:rPr><w:t xml:space=\"preserve\">Q&amp;A</w:t></w:r>"
+        );
+    }
+
+    #[test]
+    fn renders_emphasis_within_emphasis_upright() {
+        let component =
+            title("On <i>Homo</i>", Rendering { emph: Some(true), ..Default::default() });
+        assert_eq!(
+            Ooxml.render_component(&component, &RenderOptions::default()),
+            "<w:r><w:rPr><w:i/></w:rPr><w:t xml:space=\"preserve\">On </w:t></w:r>\
+             <w:r><w:rPr><w:i w:val=\"0\"/></w:rPr><w:t xml:space=\"preserve\">Homo</w:t></w:r>"
+        );
+    }
+}
@@ -0,0 +1,16 @@
+/*
+SPDX-License-Identifier: MPL-2.0
+SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
+*/
+
+use super::Renderer;
+
+/// Renders unformatted plain text.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PlainText;
+
+impl Renderer for PlainText {
+    fn text(&self, text: &str) -> String {
+        text.to_string()
+    }
+}
@@ -0,0 +1,130 @@
+/*
+SPDX-License-Identifier: MPL-2.0
+SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
+*/
+
+use super::Renderer;
+use crate::types::{ProcReferences, RenderOptions};
+
+/// Renders RTF documents, for pasting into word processors.
+///
+/// Bibliography entries are set as paragraphs with a half-inch hanging indent.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Rtf;
+
+impl Rtf {
+    fn document(&self, paragraphs: Vec<String>) -> String {
+        format!(
+            "{{\\rtf1\\ansi\\deff0{{\\fonttbl{{\\f0 Times New Roman;}}}}\n{}\n}}",
+            paragraphs.join("\n")
+        )
+    }
+}
+
+impl Renderer for Rtf {
+    fn text(&self, text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '\\' | '{' | '}' => {
+                    escaped.push('\\');
+                    escaped.push(c);
+                }
+                '\n' => escaped.push_str("\\line "),
+                c if c.is_ascii() => escaped.push(c),
+                // RTF takes Unicode as signed 16-bit code units, each followed by
+                // a fallback character for readers that cannot display it.
+                c => {
+                    let mut units = [0; 2];
+                    for unit in c.encode_utf16(&mut units) {
+                        escaped.push_str(&format!("\\u{}?", *unit as i16));
+                    }
+                }
+            }
+        }
+        escaped
+    }
+
+    fn emph(&self, content: String) -> String {
+        format!("{{\\i {}}}", content)
+    }
+
+    fn strong(&self, content: String) -> String {
+        format!("{{\\b {}}}", content)
+    }
+
+    fn link(&self, content: String, url: &str) -> String {
+        format!(
+            "{{\\field{{\\*\\fldinst HYPERLINK \"{}\"}}{{\\fldrslt {}}}}}",
+            self.text(&url.replace('"', "%22")),
+            content
+        )
+    }
+
+    fn roman(&self, content: String) -> String {
+        format!("{{\\i0 {}}}", content)
+    }
+
+    fn entry(&self, content: String) -> String {
+        format!("{{\\pard\\li720\\fi-720 {}\\par}}", content)
+    }
+
+    fn bibliography(&self, entries: Vec<String>) -> String {
+        self.document(entries)
+    }
+
+    /// Render the citations and bibliography as paragraphs of a single document.
+    fn render_references(
+        &self,
+        references: &ProcReferences,
+        options: &RenderOptions,
+    ) -> String {
+        let mut paragraphs: Vec<String> = references
+            .citations
+            .iter()
+            .flatten()
+            .map(|citation| {
+                format!("{{\\pard {}\\par}}", self.render_citation(citation, options))
+            })
+            .collect();
+        paragraphs.extend(
+            references
+                .bibliography
+                .iter()
+                .map(|entry| self.entry(self.render_template(entry, options))),
+        );
+        self.document(paragraphs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::title;
+    use csln::style::template::Rendering;
+
+    #[test]
+    fn renders_formatting() {
+        let component = title(
+            "Title",
+            Rendering {
+                emph: Some(true),
+                strong: Some(true),
+                ..Default::default()
+            },
+        );
+        let bibliography = vec![vec![component]];
+        assert_eq!(
+            Rtf.render_bibliography(&bibliography, &RenderOptions::default()),
+            "{\\rtf1\\ansi\\deff0{\\fonttbl{\\f0 Times New Roman;}}\n\
+             {\\pard\\li720\\fi-720 {\\b {\\i Title}}.\\par}\n}"
+        );
+    }
+
+    #[test]
+    fn escapes_text() {
+        assert_eq!(Rtf.text("{a\\b}"), "\\{a\\\\b\\}");
+        assert_eq!(Rtf.text("Ångström"), "\\u197?ngstr\\u246?m");
+        assert_eq!(Rtf.text("𝔸"), "\\u-10187?\\u-8904?");
+    }
+}
@@ -0,0 +1,105 @@
+/*
+SPDX-License-Identifier: MPL-2.0
+SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
+*/
+
+use super::{wrap_inline, Renderer};
+
+/// Renders [Typst](https://typst.app) markup.
+///
+/// Quotes are rendered as Typst smart quotes, so that Typst chooses the quotation
+/// marks for the document language.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Typst;
+
+impl Renderer for Typst {
+    fn text(&self, text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        // Text may start a line, as far as we know.
+        let mut line_start = true;
+        while let Some(c) = chars.next() {
+            let special = match c {
+                '\\' | '#' | '$' | '@' | '*' | '_' | '`' | '<' | '>' | '[' | ']'
+                | '~' | '"' => true,
+                // `//` and `/*` start comments
+                '/' => matches!(chars.peek(), Some('/') | Some('*')),
+                // headings, and bullet and numbered lists
+                '=' | '-' | '+' => line_start,
+                _ => false,
+            };
+            if special {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+            line_start = c == '\n' || (line_start && (c == ' ' || c == '\t'));
+        }
+        escaped
+    }
+
+    fn emph(&self, content: String) -> String {
+        wrap_inline(&content, "_", "_")
+    }
+
+    fn strong(&self, content: String) -> String {
+        wrap_inline(&content, "*", "*")
+    }
+
+    fn roman(&self, content: String) -> String {
+        format!("#text(style: \"normal\")[{}]", content)
+    }
+
+    fn quote(&self, content: String, _open: &str, _close: &str) -> String {
+        format!("\"{}\"", content)
+    }
+
+    fn inner_quote(&self, content: String, _open: &str, _close: &str) -> String {
+        format!("'{}'", content)
+    }
+
+    fn link(&self, content: String, url: &str) -> String {
+        format!(
+            "#link(\"{}\")[{}]",
+            url.replace('\\', "\\\\").replace('"', "\\\""),
+            content
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::title;
+    use crate::types::RenderOptions;
+    use csln::style::template::Rendering;
+
+    #[test]
+    fn renders_formatting() {
+        let component = title(
+            "Title",
+            Rendering {
+                emph: Some(true),
+                quote: Some(true),
+                strong: Some(true),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            Typst.render_component(&component, &RenderOptions::default()),
+            "\"*_Title_*\""
+        );
+    }
+
+    #[test]
+    fn escapes_markup() {
+        assert_eq!(Typst.text("#1 costs $5 @home"), "\\#1 costs \\$5 \\@home");
+        assert_eq!(Typst.text("a/b//c"), "a/b\\//c");
+        assert_eq!(Typst.text("= A\n - b\n+ c-d"), "\\= A\n \\- b\n\\+ c-d");
+    }
+
+    #[test]
+    fn keeps_whitespace_outside_delimiters() {
+        assert_eq!(Typst.emph(" Title ".to_string()), " _Title_ ");
+        assert_eq!(Typst.strong("Title ".to_string()), "*Title* ");
+    }
+}
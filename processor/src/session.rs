@@ -0,0 +1,357 @@
+/*
+SPDX-License-Identifier: MPL-2.0
+SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
+*/
+
+//! A long-lived processor for interactive editors.
+//!
+//! A [`Session`] holds the citations and references of a document, and reports
+//! after each change only the citations and bibliography entries whose rendering
+//! changed; including those changed by disambiguation or renumbering elsewhere in
+//! the document.
+
+use crate::processor::Processor;
+use crate::types::{
+    ProcBibliography, ProcCitation, ProcHints, ProcReferences, ProcTemplate,
+};
+use csln::bibliography::reference::InputReference;
+use csln::citation::{Citation, Position};
+use rayon::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// A processed citation, with the positions of its items it was processed for.
+type SessionCitation = (ProcCitation, Vec<(Position, bool)>);
+
+/// A citation as processed before an update, with the positions of its items; or
+/// with no positions, if the citation was edited and must be processed again.
+type PreviousCitation = (ProcCitation, Option<Vec<(Position, bool)>>);
+
+/// A processor that keeps the rendering of a document up to date as its
+/// citations and references change.
+///
+/// After each change, only the citations and bibliography entries whose input,
+/// item positions or processing hints changed are processed again.
+#[derive(Debug)]
+pub struct Session {
+    processor: Processor,
+    /// The processed citations, in document order.
+    citations: Vec<SessionCitation>,
+    /// The processed bibliography entries, by reference id.
+    entries: HashMap<String, ProcTemplate>,
+    /// The ids of the bibliography entries, in bibliography order.
+    order: Vec<String>,
+    /// The processing hints of the references, by reference id.
+    hints: HashMap<String, ProcHints>,
+}
+
+/// The changes to the rendering of a document after an update.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionUpdate {
+    /// The citations whose rendering changed, by their index in the document.
+    pub citations: Vec<(usize, ProcCitation)>,
+    /// The bibliography entries whose rendering changed, by reference id.
+    pub bibliography: Vec<(String, ProcTemplate)>,
+    /// The ids of the bibliography entries in bibliography order, if the entries
+    /// or their order changed.
+    pub bibliography_order: Option<Vec<String>>,
+}
+
+impl SessionUpdate {
+    /// Whether the update changes nothing.
+    pub fn is_empty(&self) -> bool {
+        self.citations.is_empty()
+            && self.bibliography.is_empty()
+            && self.bibliography_order.is_none()
+    }
+}
+
+impl Session {
+    /// Start a session with the citations and references of the processor.
+    pub fn new(processor: Processor) -> Session {
+        let mut session = Session {
+            processor,
+            citations: Vec::new(),
+            entries: HashMap::new(),
+            order: Vec::new(),
+            hints: HashMap::new(),
+        };
+        let previous = vec![None; session.processor.citations.len()];
+        session.refresh(previous, HashSet::new());
+        session
+    }
+
+    /// Return the current rendering of the document.
+    pub fn references(&self) -> ProcReferences {
+        let bibliography: ProcBibliography =
+            self.order.iter().map(|id| self.entries[id].clone()).collect();
+        let citations: Vec<ProcCitation> =
+            self.citations.iter().map(|(citation, _)| citation.clone()).collect();
+        let citations = if citations.is_empty() { None } else { Some(citations) };
+        ProcReferences { bibliography, citations }
+    }
+
+    /// Insert a citation at an index in the document; or return `None`, if the
+    /// index is greater than the number of citations.
+    pub fn insert_citation(
+        &mut self,
+        index: usize,
+        citation: Citation,
+    ) -> Option<SessionUpdate> {
+        if index > self.citations.len() {
+            return None;
+        }
+        self.processor.citations.insert(index, citation);
+        let mut previous = self.previous_citations();
+        previous.insert(index, None);
+        Some(self.refresh(previous, HashSet::new()))
+    }
+
+    /// Replace the citation at an index in the document; or return `None`, if
+    /// there is no citation at the index.
+    pub fn replace_citation(
+        &mut self,
+        index: usize,
+        citation: Citation,
+    ) -> Option<SessionUpdate> {
+        *self.processor.citations.get_mut(index)? = citation;
+        let mut previous = self.previous_citations();
+        if let Some((_, positions)) = &mut previous[index] {
+            *positions = None;
+        }
+        Some(self.refresh(previous, HashSet::new()))
+    }
+
+    /// Remove the citation at an index in the document; or return `None`, if
+    /// there is no citation at the index.
+    pub fn remove_citation(&mut self, index: usize) -> Option<SessionUpdate> {
+        if index >= self.citations.len() {
+            return None;
+        }
+        self.processor.citations.remove(index);
+        let mut previous = self.previous_citations();
+        previous.remove(index);
+        Some(self.refresh(previous, HashSet::new()))
+    }
+
+    /// Add a reference, or replace the reference with the same id.
+    pub fn insert_reference(
+        &mut self,
+        id: &str,
+        reference: InputReference,
+    ) -> SessionUpdate {
+        self.processor.bibliography.insert(id.to_string(), reference);
+        let previous = self.previous_citations();
+        self.refresh(previous, HashSet::from([id.to_string()]))
+    }
+
+    /// Remove a reference; citations of it are no longer rendered.
+    pub fn remove_reference(&mut self, id: &str) -> SessionUpdate {
+        self.processor.bibliography.remove(id);
+        let previous = self.previous_citations();
+        self.refresh(previous, HashSet::from([id.to_string()]))
+    }
+
+    fn previous_citations(&mut self) -> Vec<Option<PreviousCitation>> {
+        std::mem::take(&mut self.citations)
+            .into_iter()
+            .map(|(citation, positions)| Some((citation, Some(positions))))
+            .collect()
+    }
+
+    /// Process again the citations that are new, or whose item positions or
+    /// references changed, and the bibliography entries whose references
+    /// changed; and return the changes to their rendering.
+    ///
+    /// The references changed by the edit are given; those whose processing
+    /// hints changed, for example by disambiguation or renumbering, are added.
+    fn refresh(
+        &mut self,
+        previous: Vec<Option<PreviousCitation>>,
+        mut changed: HashSet<String>,
+    ) -> SessionUpdate {
+        let hints = self.processor.get_proc_hints();
+        changed.extend(
+            hints
+                .iter()
+                .filter(|(id, hint)| self.hints.get(*id) != Some(hint))
+                .map(|(id, _)| id.clone()),
+        );
+        changed.extend(self.hints.keys().filter(|id| !hints.contains_key(*id)).cloned());
+        let positions = self.processor.get_citation_positions(&self.processor.citations);
+        let mut changed_citations = Vec::new();
+        for (index, ((citation, positions), previous)) in self
+            .processor
+            .citations
+            .iter()
+            .zip(positions)
+            .zip(previous)
+            .enumerate()
+        {
+            let cites_changed = citation
+                .citation_items
+                .iter()
+                .any(|item| changed.contains(&item.ref_id));
+            let processed = match previous {
+                Some((processed, Some(previous_positions)))
+                    if previous_positions == positions && !cites_changed =>
+                {
+                    processed
+                }
+                previous => {
+                    let processed =
+                        self.processor.process_citation(citation, &positions, &hints);
+                    if previous.map(|(previous, _)| previous).as_ref() != Some(&processed)
+                    {
+                        changed_citations.push((index, processed.clone()));
+                    }
+                    processed
+                }
+            };
+            self.citations.push((processed, positions));
+        }
+        let references = self.processor.get_bibliography_references();
+        let order: Vec<String> = references.iter().filter_map(|r| r.id()).collect();
+        let changed_entries: Vec<(String, ProcTemplate)> = references
+            .par_iter()
+            .filter_map(|reference| {
+                let id = reference.id()?;
+                if self.entries.contains_key(&id) && !changed.contains(&id) {
+                    return None;
+                }
+                let entry = self.processor.process_reference(reference, &hints);
+                (self.entries.get(&id) != Some(&entry)).then_some((id, entry))
+            })
+            .collect();
+        let ids: HashSet<&String> = order.iter().collect();
+        self.entries.retain(|id, _| ids.contains(id));
+        self.entries.extend(changed_entries.iter().cloned());
+        let bibliography_order =
+            if order != self.order { Some(order.clone()) } else { None };
+        self.order = order;
+        self.hints = hints;
+        SessionUpdate {
+            citations: changed_citations,
+            bibliography: changed_entries,
+            bibliography_order,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::author_year_style;
+    use crate::render::{PlainText, Renderer};
+    use csln::bibliography::reference::{
+        Contributor, EdtfString, Monograph, MonographType, StructuredName, Title,
+    };
+    use csln::citation::CitationItem;
+    use csln::style::locale::Locale;
+    use csln::style::template::WrapPunctuation;
+
+    fn reference(id: &str, family: &str, year: &str) -> InputReference {
+        InputReference::Monograph(Monograph {
+            id: Some(id.to_string()),
+            r#type: MonographType::Book,
+            author: Some(Contributor::StructuredName(StructuredName {
+                family: family.to_string(),
+                given: "Jane".to_string(),
+            })),
+            issued: EdtfString(year.to_string()),
+            title: Title::Single(format!("Title {}", id)),
+            publisher: None,
+            url: None,
+            accessed: None,
+            note: None,
+            isbn: None,
+            doi: None,
+            edition: None,
+            translator: None,
+        })
+    }
+
+    fn cite(id: &str) -> Citation {
+        Citation {
+            citation_items: vec![CitationItem {
+                ref_id: id.to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn session() -> Session {
+        let citation = csln::style::Citation {
+            wrap: Some(WrapPunctuation::Parentheses),
+            ..Default::default()
+        };
+        let mut style = author_year_style(citation, None);
+        // Entries render the same template as citations.
+        let template = style.citation.as_ref().map(|citation| citation.template.clone());
+        style.bibliography = template
+            .map(|template| csln::style::Bibliography { options: None, template });
+        let bibliography =
+            [reference("doe1", "Doe", "2020"), reference("roe", "Roe", "2019")]
+                .into_iter()
+                .map(|reference| (reference.id().unwrap_or_default(), reference))
+                .collect();
+        Session::new(Processor::new(style, bibliography, Vec::new(), Locale::default()))
+    }
+
+    fn render(update: &SessionUpdate) -> Vec<(usize, String)> {
+        let options = Processor::default().render_options();
+        update
+            .citations
+            .iter()
+            .map(|(index, citation)| {
+                (*index, PlainText.render_citation(citation, &options))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reports_changed_citations() {
+        let mut session = session();
+        let update = session.insert_citation(0, cite("doe1")).unwrap_or_default();
+        assert_eq!(render(&update), vec![(0, "(Doe 2020)".to_string())]);
+        assert_eq!(update.bibliography_order, Some(vec!["doe1".to_string()]));
+        let update = session.insert_citation(1, cite("roe")).unwrap_or_default();
+        assert_eq!(render(&update), vec![(1, "(Roe 2019)".to_string())]);
+        assert_eq!(update.bibliography.len(), 1);
+        // An unchanged citation changes nothing.
+        assert_eq!(
+            session.replace_citation(1, cite("roe")),
+            Some(SessionUpdate::default())
+        );
+        // A new reference by the same author in the same year disambiguates the
+        // earlier citation.
+        session.insert_reference("doe2", reference("doe2", "Doe", "2020"));
+        let update = session.insert_citation(0, cite("doe2")).unwrap_or_default();
+        let rendered = render(&update);
+        assert_eq!(rendered.len(), 2);
+        assert_eq!(rendered[1].0, 1);
+        assert!(rendered.iter().all(|(_, citation)| citation.starts_with("(Doe 2020")));
+        assert_eq!(update.bibliography.len(), 2);
+        let update = session.remove_citation(0).unwrap_or_default();
+        assert_eq!(render(&update), vec![(0, "(Doe 2020)".to_string())]);
+        assert_eq!(
+            update.bibliography_order,
+            Some(vec!["doe1".to_string(), "roe".to_string()])
+        );
+        assert_eq!(session.references().citations.unwrap_or_default().len(), 2);
+    }
+
+    #[test]
+    fn rejects_out_of_range_citation_indices() {
+        let mut session = session();
+        assert_eq!(session.insert_citation(1, cite("doe1")), None);
+        assert_eq!(session.replace_citation(0, cite("doe1")), None);
+        assert_eq!(session.remove_citation(0), None);
+        assert!(session.insert_citation(0, cite("doe1")).is_some());
+        assert_eq!(session.remove_citation(1), None);
+        assert_eq!(session.references().citations.unwrap_or_default().len(), 1);
+    }
+}
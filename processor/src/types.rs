@@ -5,9 +5,10 @@ SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
 
 
 
+use csln::citation::{CitationModeType, Position};
 use csln::style::locale::Locale;
-use csln::style::options::Config;
-use csln::style::template::TemplateComponent;
+use csln::style::options::{Config, GivenNameForm};
+use csln::style::template::{TemplateComponent, WrapPunctuation};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -18,7 +19,7 @@ pub type ProcTemplate = Vec<ProcTemplateComponent>;
 
 /// The intermediate representation of a StyleTemplateComponent, which is used to render the output.
 /// This struct will have two fields: a StyleComponent and a String.
-#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ProcTemplateComponent {
     /// The original input style template component, which provides rendering instructions.
@@ -33,7 +34,7 @@ impl ProcTemplateComponent {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema, PartialEq)]
 #[serde(rename_all = "camelCase")]
 /// Holds one or more processed strings, ready for final rendering.
 pub struct ProcValues {
@@ -43,9 +44,11 @@ pub struct ProcValues {
     pub prefix: Option<String>,
     /// The suffix to render.
     pub suffix: Option<String>,
+    /// The URL to link the value to, where the output format supports links.
+    pub url: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 /// Holds the intermediate processing hints for a reference that can be used
 /// to render the output; particularly for disambiguation.
@@ -58,6 +61,14 @@ pub struct ProcHints {
     pub group_length: usize,
     /// The key of the group.
     pub group_key: String,
+    /// The number of the reference, for numeric styles.
+    pub citation_number: Option<usize>,
+    /// Whether to render all the author names, to tell the reference apart from
+    /// those of other authors.
+    pub expand_names: bool,
+    /// The form of the given names to add to the author names, to tell the
+    /// reference apart from those of other authors.
+    pub given_names: GivenNameForm,
 }
 
 impl ProcHints {
@@ -72,6 +83,9 @@ impl ProcHints {
             group_index,
             group_length,
             group_key,
+            citation_number: None,
+            expand_names: false,
+            given_names: GivenNameForm::None,
         }
     }
 }
@@ -83,6 +97,9 @@ impl Default for ProcHints {
             group_index: 0,
             group_length: 0,
             group_key: "".to_string(),
+            citation_number: None,
+            expand_names: false,
+            given_names: GivenNameForm::None,
         }
     }
 }
@@ -107,6 +124,45 @@ pub struct ProcReferences {
 }
 
 pub type ProcBibliography = Vec<ProcTemplate>;
-pub type ProcCitationItem = Vec<ProcTemplateComponent>;
-pub type ProcCitation = Vec<ProcCitationItem>;
 pub type ProcCitations = Vec<ProcCitation>;
+
+/// The intermediate representation of a citation, with the punctuation to
+/// render it with.
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcCitation {
+    /// The string that prefaces the citation items.
+    pub prefix: Option<String>,
+    /// The processed citation items.
+    pub items: Vec<ProcCitationItem>,
+    /// The string that follows the citation items.
+    pub suffix: Option<String>,
+    /// Whether the citation is integral (narrative) or not.
+    pub mode: CitationModeType,
+    /// The delimiter between the citation items.
+    pub delimiter: String,
+    /// The punctuation to wrap the citation in.
+    pub wrap: WrapPunctuation,
+}
+
+/// The intermediate representation of a citation item.
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcCitationItem {
+    /// The string that prefaces the item.
+    pub prefix: Option<String>,
+    /// The processed components of the item.
+    pub template: ProcTemplate,
+    /// The locators that follow the item.
+    pub suffix: Option<String>,
+    /// The delimiter between the components of the item.
+    pub delimiter: String,
+    /// The delimiter that precedes the item in place of the citation delimiter,
+    /// when it is collapsed into the preceding items by the same author.
+    pub group_delimiter: Option<String>,
+    /// The position of the item relative to the earlier citations of its reference.
+    pub position: Position,
+    /// Whether the previous citation of the reference is within the near-note
+    /// distance of the style.
+    pub near_note: bool,
+}
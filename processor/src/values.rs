@@ -4,13 +4,16 @@ SPDX-FileCopyrightText: © 2023 Bruce D'Arcus
 */
 
 use crate::types::{ProcHints, RenderOptions, ProcValues};
-use csln::bibliography::reference::{InputReference, EdtfString};
+use csln::bibliography::reference::{Contributor, InputReference, EdtfString};
 use csln::style::locale::Locale;
-use csln::style::options::{MonthFormat};
+use csln::citation::LocatorTerm;
+use csln::style::options::{
+    DoiForm, LocatorLabel, LocatorLabelForm, LocatorOptions, MonthFormat,
+};
 use csln::style::template::{
-    ContributorForm, ContributorRole, DateForm, Dates, Numbers, TemplateComponent,
-    TemplateContributor, TemplateDate, TemplateNumber, TemplateSimpleString,
-    TemplateTitle, Titles, Variables,
+    ContributorForm, ContributorRole, DateForm, Dates, GeneralTerm, Numbers,
+    TemplateComponent, TemplateContributor, TemplateDate, TemplateNumber,
+    TemplateSimpleString, TemplateTerm, TemplateTitle, Titles, Variables,
 };
 use icu::datetime::DateTimeFormatterOptions;
 
@@ -40,6 +43,7 @@ impl ComponentValues for TemplateComponent {
             TemplateComponent::SimpleString(string) => {
                 string.values(reference, hints, options)
             }
+            TemplateComponent::Term(term) => term.values(reference, hints, options),
             TemplateComponent::List(_list) => todo!(),
             _ => None,
         };
@@ -47,6 +51,7 @@ impl ComponentValues for TemplateComponent {
             value: proc_values.as_ref()?.value.clone(),
             prefix: proc_values.as_ref()?.prefix.clone(),
             suffix: proc_values.as_ref()?.suffix.clone(),
+            url: proc_values.as_ref()?.url.clone(),
         })
     }
 }
@@ -55,7 +60,7 @@ impl ComponentValues for TemplateNumber {
     fn values(
         &self,
         reference: &InputReference,
-        _hints: &ProcHints,
+        hints: &ProcHints,
         _options: &RenderOptions,
     ) -> Option<ProcValues> {
         let number: Option<String> = match &self.number {
@@ -80,11 +85,13 @@ impl ComponentValues for TemplateNumber {
                 }
                 _ => None,
             },
+            Numbers::CitationNumber => hints.citation_number.map(|n| n.to_string()),
         };
         Some(ProcValues {
             value: number.unwrap_or_default(),
             prefix: None,
             suffix: None,
+            url: None,
         })
     }
 }
@@ -94,34 +101,112 @@ impl ComponentValues for TemplateSimpleString {
         &self,
         reference: &InputReference,
         _hints: &ProcHints,
-        _options: &RenderOptions,
+        options: &RenderOptions,
     ) -> Option<ProcValues> {
+        let mut url = None;
         let value = match self.variable {
-            Variables::Doi => match reference {
-                InputReference::SerialComponent(serial_component) => {
-                    Some(serial_component.doi.as_ref()?.to_string())
-                }
-                InputReference::CollectionComponent(monograph_component) => {
-                    Some(monograph_component.doi.as_ref()?.to_string())
+            Variables::Doi => {
+                let doi = bare_doi(&reference.doi()?);
+                url = Some(format!("https://doi.org/{}", doi));
+                match options.global.links.clone().unwrap_or_default().doi {
+                    DoiForm::Url => url.clone(),
+                    DoiForm::Bare => Some(doi),
                 }
-                _ => None,
-            },
+            }
             Variables::Isbn => match reference {
                 InputReference::Monograph(monograph_component) => {
                     Some(monograph_component.isbn.as_ref()?.to_string())
                 }
                 _ => None,
             },
+            Variables::Url => {
+                url = Some(reference.url()?.to_string());
+                url.clone()
+            }
             _ => None, // TODO completes
         };
         Some(ProcValues {
             value: value.unwrap_or_default(),
             prefix: None,
             suffix: None,
+            url,
+        })
+    }
+}
+
+impl ComponentValues for TemplateTerm {
+    fn values(
+        &self,
+        _reference: &InputReference,
+        _hints: &ProcHints,
+        options: &RenderOptions,
+    ) -> Option<ProcValues> {
+        let terms = &options.locale.terms;
+        let value = match self.term {
+            GeneralTerm::Ibid => terms.ibid.clone(),
+            GeneralTerm::LocCit => terms.loc_cit.clone(),
+            GeneralTerm::OpCit => terms.op_cit.clone(),
+        };
+        Some(ProcValues {
+            value: value?,
+            prefix: None,
+            suffix: None,
+            url: None,
         })
     }
 }
 
+/// Return the year suffix for a position in a group, starting at 1: "a" to "z",
+/// then "aa", "ab" and so on.
+fn year_suffix(index: usize) -> String {
+    let mut letters = Vec::new();
+    let mut n = index;
+    while n > 0 {
+        n -= 1;
+        letters.push((b'a' + (n % 26) as u8) as char);
+        n /= 26;
+    }
+    letters.iter().rev().collect()
+}
+
+#[test]
+fn year_suffixes() {
+    assert_eq!(year_suffix(1), "a");
+    assert_eq!(year_suffix(26), "z");
+    assert_eq!(year_suffix(27), "aa");
+    assert_eq!(year_suffix(28), "ab");
+    assert_eq!(year_suffix(702), "zz");
+    assert_eq!(year_suffix(703), "aaa");
+}
+
+/// Strip any resolver URL or "doi:" prefix from a DOI.
+fn bare_doi(doi: &str) -> String {
+    let doi = doi.trim();
+    let prefixes = [
+        "https://doi.org/",
+        "http://doi.org/",
+        "https://dx.doi.org/",
+        "http://dx.doi.org/",
+        "doi:",
+    ];
+    for prefix in prefixes {
+        if let Some(start) = doi.get(..prefix.len()) {
+            if start.eq_ignore_ascii_case(prefix) {
+                return doi[prefix.len()..].trim_start().to_string();
+            }
+        }
+    }
+    doi.to_string()
+}
+
+#[test]
+fn normalizes_doi() {
+    assert_eq!(bare_doi("https://doi.org/10.1000/182"), "10.1000/182");
+    assert_eq!(bare_doi("http://dx.doi.org/10.1000/182"), "10.1000/182");
+    assert_eq!(bare_doi("doi: 10.1000/182"), "10.1000/182");
+    assert_eq!(bare_doi("10.1000/182"), "10.1000/182");
+}
+
 impl ComponentValues for TemplateTitle {
     fn values(
         &self,
@@ -164,6 +249,7 @@ impl ComponentValues for TemplateTitle {
             value: value.unwrap_or_default(),
             prefix: None,
             suffix: None,
+            url: None,
         })
     }
 }
@@ -223,22 +309,107 @@ fn role_form_to_string() {
     assert_eq!(result, Some("editor".to_string()));
 }
 
+/// Format a locator, with its localized label as configured; for example, "p. 42"
+/// or "pp. 42–45".
+pub fn format_locator(
+    term: &LocatorTerm,
+    value: &str,
+    locale: &Locale,
+    options: &LocatorOptions,
+) -> String {
+    // Ranges use an en dash.
+    let chars: Vec<char> = value.chars().collect();
+    let value: String = chars
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| {
+            let between_alphanumerics = i > 0
+                && chars[i - 1].is_alphanumeric()
+                && chars.get(i + 1).is_some_and(|next| next.is_alphanumeric());
+            if c == '-' && between_alphanumerics {
+                '–'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let show_label = match options.label {
+        LocatorLabel::Always => true,
+        LocatorLabel::Contextual => term != &LocatorTerm::Page,
+        LocatorLabel::Never => false,
+    };
+    let Some(forms) = locale.locators.get(term).filter(|_| show_label) else {
+        return value;
+    };
+    let plural = value.contains(['–', ',', '&']);
+    let term = if plural { &forms.plural } else { &forms.singular };
+    let label = match options.form {
+        LocatorLabelForm::Long => &term.long,
+        LocatorLabelForm::Short => &term.short,
+    };
+    format!("{} {}", label, value)
+}
+
+#[test]
+fn locator_to_string() {
+    use csln::style::locale::{LocatorTermForms, SimpleTerm};
+    let mut locale = Locale::default();
+    locale.locators.insert(
+        LocatorTerm::Page,
+        LocatorTermForms {
+            singular: SimpleTerm { long: "page".to_string(), short: "p.".to_string() },
+            plural: SimpleTerm {
+                long: "pages".to_string(),
+                short: "pp.".to_string(),
+            },
+        },
+    );
+    let mut options = LocatorOptions::default();
+    assert_eq!(format_locator(&LocatorTerm::Page, "42", &locale, &options), "p. 42");
+    assert_eq!(
+        format_locator(&LocatorTerm::Page, "42-45", &locale, &options),
+        "pp. 42–45"
+    );
+    // Without a localized label, the value renders alone.
+    assert_eq!(format_locator(&LocatorTerm::Chapter, "3", &locale, &options), "3");
+    options.label = LocatorLabel::Contextual;
+    assert_eq!(format_locator(&LocatorTerm::Page, "42", &locale, &options), "42");
+    options.label = LocatorLabel::Always;
+    options.form = LocatorLabelForm::Long;
+    assert_eq!(
+        format_locator(&LocatorTerm::Page, "42, 45", &locale, &options),
+        "pages 42, 45"
+    );
+}
+
 impl ComponentValues for TemplateContributor {
     fn values(
         &self,
         reference: &InputReference,
-        _hints: &ProcHints,
+        hints: &ProcHints,
         options: &RenderOptions,
     ) -> Option<ProcValues> {
         let locale = options.locale.clone();
+        // The short form of author names is the family names only, unless more is
+        // needed to tell authors apart.
+        let format_author = |contributor: Contributor, locale: Locale| match self.form {
+            ContributorForm::Short => contributor.format_cite_names(
+                options.global.clone(),
+                locale,
+                hints.given_names,
+                hints.expand_names,
+            ),
+            _ => contributor.format(options.global.clone(), locale),
+        };
         match &self.contributor {
             ContributorRole::Author => {
                 let author = reference.author();
                 if author.is_some() {
                     Some(ProcValues {
-                        value: author?.format(options.global.clone(), locale),
+                        value: format_author(author?, locale),
                         prefix: None,
                         suffix: None,
+                        url: None,
                     })
                 } else {
                     // TODO generalize the substitution
@@ -262,9 +433,10 @@ impl ComponentValues for TemplateContributor {
                     })); // TODO fix this matching logic
                     
                     Some(ProcValues {
-                        value: editor.format(options.global.clone(), locale),
+                        value: format_author(editor, locale),
                         prefix: None,
                         suffix: suffix_padded,
+                        url: None,
                     })
                 }
             }
@@ -297,6 +469,7 @@ impl ComponentValues for TemplateContributor {
                                     value: editor.format(options.global.clone(), locale),
                                     prefix: prefix_padded,
                                     suffix: None,
+                                    url: None,
                                 })
                             }
                             _ => {
@@ -317,6 +490,7 @@ impl ComponentValues for TemplateContributor {
                                     value: editor.format(options.global.clone(), locale),
                                     prefix: None,
                                     suffix: suffix_padded, // TODO handle None
+                                    url: None,
                                 })
                             }
                         }
@@ -327,11 +501,13 @@ impl ComponentValues for TemplateContributor {
                 value: reference.translator()?.format(options.global.clone(), locale),
                 prefix: None,
                 suffix: None,
+                url: None,
             }),
             ContributorRole::Publisher => Some(ProcValues {
                 value: reference.publisher()?.format(options.global.clone(), locale),
                 prefix: None,
                 suffix: None,
+                url: None,
             }),
             // TODO implement the rest
             _ => None,
@@ -378,22 +554,14 @@ impl ComponentValues for TemplateDate {
             };
         }
 
-        fn int_to_letter(n: u32) -> String {
-            let c = n + 96;
-            match char::from_u32(c) {
-                Some(ch) => ch.to_string(),
-                None => "".to_string(),
-            }
-        }
-
-        let suffix = if hints.disamb_condition
-            // TODO need to check form here also
-            // && self.form == style::template::DateForm::Year
+        // The year suffix goes with the year of the issued date.
+        let has_year = self.date == Dates::Issued && self.form != DateForm::MonthDay;
+        let suffix = if has_year
+            && hints.disamb_condition
             // REVIEW: ugly, and needs to be smarter
             && options.global.processing.clone().unwrap_or_default().config().disambiguate.unwrap_or_default().year_suffix
-            && formatted_date.len() == 4
         {
-            int_to_letter((hints.group_index % 26) as u32)
+            year_suffix(hints.group_index)
         } else {
             "".to_string()
         };
@@ -401,6 +569,7 @@ impl ComponentValues for TemplateDate {
             value: formatted_date,
             prefix: None,
             suffix: Some(suffix), // put the suffix here, in case we need to do something with it
+            url: None,
         })
     }
 }
@@ -3,6 +3,7 @@ mod tests {
     use anyhow::Context;
     use csln::citation::{Citation, CitationItem, Citations};
     use csln::from_file;
+    use csln_processor::Selection;
 
     #[allow(dead_code)]
     // FIXME why these warnings?
@@ -56,21 +57,86 @@ mod tests {
             label: None,
             prefix: Some("Prefix".to_string()),
             suffix: None,
+            suppress_author: false,
+            author_only: false,
         };
         let result = fixture.processor.process_citation_item(&citation_item);
         // confirm
         // assert_eq!(fixture.processor.get_reference("doe1"), "doe1".to_string());
-        assert_eq!(result.unwrap()[0].values.value.to_string(), "Doe, Jane".to_string());
+        let result = result.unwrap();
+        assert_eq!(result.prefix, Some("Prefix".to_string()));
+        assert_eq!(result.template[0].values.value.to_string(), "Doe".to_string());
+    }
+
+    #[test]
+    fn process_citation_item_author_modes() {
+        let fixture = setup();
+        let citation_item = CitationItem {
+            ref_id: "doe1".to_string(),
+            suppress_author: true,
+            ..Default::default()
+        };
+        let result = fixture.processor.process_citation_item(&citation_item).unwrap();
+        assert!(result.template.iter().all(|c| !c.template_component.is_author()));
+        assert!(!result.template.is_empty());
+        let citation_item = CitationItem {
+            ref_id: "doe1".to_string(),
+            author_only: true,
+            ..Default::default()
+        };
+        let result = fixture.processor.process_citation_item(&citation_item).unwrap();
+        assert_eq!(result.template.len(), 1);
+        assert_eq!(result.template[0].values.value, "Doe");
     }
 
     #[test]
     fn derives_proc_hints() {
         let fixture = setup();
-        let proc_hints = fixture.processor.get_proc_hints();
+        let processor = fixture.processor.with_selection(Selection::All);
+        let proc_hints = processor.get_proc_hints();
         assert_eq!(proc_hints["doe7"].group_index, 1);
         assert_eq!(proc_hints["doe7"].group_length, 1);
     }
 
+    #[test]
+    fn assigns_year_suffixes_by_title() {
+        use csln_processor::{PlainText, Renderer};
+        let fixture = setup();
+        let references = fixture.processor.process_references();
+        let options = fixture.processor.render_options();
+        let citations: Vec<String> = references
+            .citations
+            .unwrap_or_default()
+            .iter()
+            .map(|citation| PlainText.render_citation(citation, &options))
+            .collect();
+        // doe3 is "Title 0" and doe2 is "Title 1", both from 2020.
+        assert_eq!(citations[0], "(Doe 2023; Doe 2020b)");
+        assert_eq!(citations[2], "(see Doe 2020a)");
+        let entries: Vec<String> = references
+            .bibliography
+            .iter()
+            .map(|entry| PlainText.render_template(entry, &options))
+            .collect();
+        assert!(entries[0].starts_with("Doe, Jane. (2020a). Title 0."));
+        assert!(entries[1].starts_with("Doe, Jane. (2020b). Title 1."));
+    }
+
+    #[test]
+    fn selects_references() {
+        let fixture = setup();
+        let cited = fixture.processor.process_references().bibliography;
+        assert_eq!(cited.len(), 3);
+        let processor = fixture.processor.with_selection(Selection::with_nocite(vec![
+            "doe7".to_string(),
+            "doe1".to_string(),
+        ]));
+        assert_eq!(processor.process_references().bibliography.len(), 4);
+        let all = Selection::with_nocite(vec!["*".to_string()]);
+        let processor = processor.with_selection(all);
+        assert_eq!(processor.process_references().bibliography.len(), 36);
+    }
+
     #[test]
     fn loads_and_parses_locale_file() {
         let fixture = setup();